@@ -1,13 +1,21 @@
 use bevy_pancam::{PanCam, PanCamPlugin};
 
-use crate::prelude::*;
+use crate::{
+    keymap::{Action, Keymap},
+    prelude::*,
+    text_edit::not_editing_text,
+};
+
+/// How much each zoom shortcut scales the view.
+const ZOOM_STEP: f32 = 1.25;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(PanCamPlugin)
-            .add_systems(Startup, setup_camera);
+            .add_systems(Startup, setup_camera)
+            .add_systems(Update, zoom_shortcuts.run_if(not_editing_text));
     }
 }
 
@@ -31,3 +39,29 @@ fn setup_camera(mut commands: Commands) {
         ..Default::default()
     });
 }
+
+fn zoom_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut camera_query: Query<(&PanCam, &mut OrthographicProjection)>,
+) {
+    let Ok((pan_cam, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let scale = if keymap.just_pressed(Action::ZoomIn, &keys) {
+        projection.scale / ZOOM_STEP
+    } else if keymap.just_pressed(Action::ZoomOut, &keys) {
+        projection.scale * ZOOM_STEP
+    } else if keymap.just_pressed(Action::ResetZoom, &keys) {
+        // A scale of 1 shows the board at its actual size
+        1.
+    } else {
+        return;
+    };
+
+    projection.scale = scale.clamp(
+        pan_cam.min_scale,
+        pan_cam.max_scale.unwrap_or(f32::INFINITY),
+    );
+}
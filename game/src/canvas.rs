@@ -1,15 +1,25 @@
 use bevy::window::PrimaryWindow;
 use bevy_pancam::PanCam;
 
-use crate::events::ResizeEvent;
+use crate::events::{DropItemsEvent, ResizeEvent};
+use crate::item::SpawnItemEvent;
 use crate::prelude::*;
 
+/// How far apart items dropped together are placed.
+const DROP_OFFSET: Vec2 = Vec2::new(40., -40.);
+
 pub struct CanvasPlugin;
 
 impl Plugin for CanvasPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CursorCoords>()
-            .add_systems(Update, (update_window_resolution, cursor_world_coords));
+        app.init_resource::<CursorCoords>().add_systems(
+            Update,
+            (
+                update_window_resolution,
+                cursor_world_coords,
+                place_dropped_items,
+            ),
+        );
     }
 }
 
@@ -76,3 +86,29 @@ fn cursor_world_coords(
         cursor_coords.hold_start = None;
     }
 }
+
+fn place_dropped_items(
+    mut events: EventReader<DropItemsEvent>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<PanCam>>,
+    mut spawn_item_writer: EventWriter<SpawnItemEvent>,
+) {
+    // Drops are left unread until the camera is there, e.g. during startup
+    let Ok((camera, camera_transform)) = q_camera.get_single() else {
+        return;
+    };
+
+    for event in events.read() {
+        let Some(world_position) =
+            camera.viewport_to_world_2d(camera_transform, Vec2::new(event.x, event.y))
+        else {
+            continue;
+        };
+
+        for (index, item) in event.items.iter().enumerate() {
+            spawn_item_writer.send(SpawnItemEvent {
+                item: item.clone(),
+                position: Some(world_position + DROP_OFFSET * index as f32),
+            });
+        }
+    }
+}
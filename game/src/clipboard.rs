@@ -0,0 +1,233 @@
+use bevy::{hierarchy::despawn_with_children_recursive, render::primitives::Aabb, utils::HashSet};
+
+use crate::{
+    canvas::CursorCoords,
+    color_swatch::parse_pasted_color,
+    document::BoardDocument,
+    events::{AddItemEvent, OutputEvent, PasteEvent, TxOutputEvent},
+    item::{ItemDeletion, SpawnItemEvent},
+    keymap::{Action, Keymap},
+    prelude::*,
+    select::{components::Selected, utils::get_surrounding_rect},
+    text_edit::{not_editing_text, TextEditor},
+    theme::Theme,
+    FontStack, ImageCache,
+};
+
+/// Marks clipboard text as items copied from a board.
+const CLIPBOARD_PREFIX: &str = "moodboard-items:";
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(40., -40.);
+
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemClipboard>()
+            .add_event::<ClipboardEvent>()
+            .add_event::<PasteItemsEvent>()
+            .add_systems(
+                Update,
+                (
+                    clipboard_shortcuts.run_if(not_editing_text),
+                    // Cutting deletes the copied items
+                    copy_selection
+                        .after(clipboard_shortcuts)
+                        .in_set(ItemDeletion::Delete),
+                    paste_clipboard,
+                    paste_items.after(copy_selection).after(paste_clipboard),
+                ),
+            );
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ClipboardEvent {
+    Copy,
+    Cut,
+    Duplicate,
+}
+
+/// Items to spawn and select, `board` positions are relative to `position`.
+#[derive(Event, Clone, Debug)]
+struct PasteItemsEvent {
+    board: BoardDocument,
+    position: Vec2,
+}
+
+/// The items last copied, positioned relative to the centre of their surrounding rect.
+#[derive(Resource, Default)]
+struct ItemClipboard {
+    copied: Option<BoardDocument>,
+}
+
+fn clipboard_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut clipboard_writer: EventWriter<ClipboardEvent>,
+) {
+    // Pasting is started by the web app, it has to read the system clipboard first
+    if keymap.just_pressed(Action::Copy, &keys) {
+        clipboard_writer.send(ClipboardEvent::Copy);
+    }
+    if keymap.just_pressed(Action::Cut, &keys) {
+        clipboard_writer.send(ClipboardEvent::Cut);
+    }
+    if keymap.just_pressed(Action::Duplicate, &keys) {
+        clipboard_writer.send(ClipboardEvent::Duplicate);
+    }
+}
+
+fn copy_selection(world: &mut World) {
+    let events = world
+        .resource_mut::<Events<ClipboardEvent>>()
+        .drain()
+        .collect::<Vec<_>>();
+
+    for event in events {
+        let Some((board, center)) = capture_selection(world) else {
+            continue;
+        };
+
+        match event {
+            ClipboardEvent::Copy | ClipboardEvent::Cut => {
+                match board.to_json() {
+                    Ok(json) => {
+                        if let Some(tx_output_event) = world.get_resource::<TxOutputEvent>() {
+                            tx_output_event.emit(OutputEvent::CopyToClipboard(format!(
+                                "{CLIPBOARD_PREFIX}{json}"
+                            )));
+                        }
+                    }
+                    Err(err) => warn!("Could not serialize copied items: {}", err),
+                }
+                world.resource_mut::<ItemClipboard>().copied = Some(board);
+
+                if event == ClipboardEvent::Cut {
+                    let selected = world
+                        .query_filtered::<Entity, With<Selected>>()
+                        .iter(world)
+                        .collect::<Vec<_>>();
+                    for entity in selected {
+                        despawn_with_children_recursive(world, entity);
+                    }
+                }
+            }
+            ClipboardEvent::Duplicate => {
+                world
+                    .resource_mut::<Events<PasteItemsEvent>>()
+                    .send(PasteItemsEvent {
+                        board,
+                        position: center + DUPLICATE_OFFSET,
+                    });
+            }
+        }
+    }
+}
+
+/// Captures the selected items relative to the centre of their surrounding rect, and that
+/// centre.
+fn capture_selection(world: &mut World) -> Option<(BoardDocument, Vec2)> {
+    let mut selected_query =
+        world.query_filtered::<(Entity, &GlobalTransform, &Aabb), With<Selected>>();
+    let selected = selected_query.iter(world).collect::<Vec<_>>();
+
+    let rect = get_surrounding_rect(
+        selected
+            .iter()
+            .map(|(_, transform, aabb)| (*transform, *aabb))
+            .collect(),
+    )?;
+    let entities = selected
+        .iter()
+        .map(|(entity, _, _)| *entity)
+        .collect::<HashSet<_>>();
+
+    let mut board = BoardDocument::capture_where(world, |entity| entities.contains(&entity.id()));
+    for item in &mut board.items {
+        item.position -= rect.center();
+    }
+
+    Some((board, rect.center()))
+}
+
+fn paste_clipboard(
+    mut events: EventReader<PasteEvent>,
+    cursor_coords: Res<CursorCoords>,
+    item_clipboard: Res<ItemClipboard>,
+    mut spawn_item_writer: EventWriter<SpawnItemEvent>,
+    mut paste_items_writer: EventWriter<PasteItemsEvent>,
+    editor_query: Query<(), With<TextEditor>>,
+) {
+    // Text pasted while editing goes into the edited text instead
+    if !editor_query.is_empty() {
+        events.clear();
+        return;
+    }
+
+    for event in events.read() {
+        let item = match event {
+            PasteEvent::Image(path) => AddItemEvent::Image(path.clone()),
+            PasteEvent::Text(text) => {
+                if let Some(board) = text
+                    .strip_prefix(CLIPBOARD_PREFIX)
+                    .and_then(|json| BoardDocument::from_json(json).ok())
+                {
+                    paste_items_writer.send(PasteItemsEvent {
+                        board,
+                        position: cursor_coords.current,
+                    });
+                    continue;
+                }
+
+                if parse_pasted_color(text).is_some() {
+                    AddItemEvent::Swatch(text.clone())
+                } else {
+                    AddItemEvent::PostIt(text.clone())
+                }
+            }
+            PasteEvent::Copied => {
+                if let Some(board) = &item_clipboard.copied {
+                    paste_items_writer.send(PasteItemsEvent {
+                        board: board.clone(),
+                        position: cursor_coords.current,
+                    });
+                }
+                continue;
+            }
+        };
+
+        spawn_item_writer.send(SpawnItemEvent {
+            item,
+            position: Some(cursor_coords.current),
+        });
+    }
+}
+
+fn paste_items(
+    mut commands: Commands,
+    mut events: EventReader<PasteItemsEvent>,
+    selected_query: Query<Entity, With<Selected>>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    asset_server: Res<AssetServer>,
+    mut image_cache: ResMut<ImageCache>,
+) {
+    for event in events.read() {
+        for entity in &selected_query {
+            commands.entity(entity).remove::<Selected>();
+        }
+
+        // Copies are new items, pasting the same items twice must not share ids
+        let board = event.board.pasted(event.position);
+        let spawned = board.spawn(
+            &mut commands,
+            &theme,
+            &font_stack,
+            &asset_server,
+            &mut image_cache,
+        );
+        for (entity, item) in spawned {
+            commands.entity(entity).insert(Selected::new(item.position));
+        }
+    }
+}
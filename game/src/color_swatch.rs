@@ -3,7 +3,7 @@ use rand::seq::SliceRandom;
 
 use crate::{
     events::AddItemEvent,
-    item::ItemBundle,
+    item::{item_aabb, ItemBundle, ItemOutlineQuery, ItemSize, SpawnItemEvent},
     post_it::PostItShadow,
     prelude::*,
     theme::{Theme, ThemeDidChange},
@@ -42,7 +42,7 @@ pub struct ColorSwatchPlugin;
 
 impl Plugin for ColorSwatchPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (add_swatch, on_theme_change));
+        app.add_systems(Update, (add_swatch, on_theme_change, resize_swatches));
     }
 }
 
@@ -52,13 +52,16 @@ pub struct ColorSwatch;
 #[derive(Component)]
 pub struct ColorSwatchText;
 
+#[derive(Component)]
+pub struct ColorSwatchColor;
+
 pub(crate) fn spawn_swatch(
     commands: &mut Commands,
     theme: &Theme,
     font_stack: &FontStack,
     position: Vec3,
     color: &Color,
-) {
+) -> Entity {
     let rgba = color.as_rgba_u8();
     let text = format!("#{:x?}{:x?}{:x?}", rgba[0], rgba[1], rgba[2]);
 
@@ -84,6 +87,7 @@ pub(crate) fn spawn_swatch(
                 ..Default::default()
             },
             ColorSwatch,
+            ItemSize(SWATCH_SIZE),
             Name::new("Swatch"),
         ))
         .with_children(|builder| {
@@ -93,25 +97,20 @@ pub(crate) fn spawn_swatch(
                         extents: SWATCH_COLOR_SECTION_SIZE,
                         ..Default::default()
                     }),
-                    spatial: SpatialBundle::from_transform(Transform::from_translation(Vec3::new(
-                        0.,
-                        (SWATCH_SIZE.y / 2.) - (SWATCH_COLOR_SECTION_SIZE.y / 2.) - 2.,
-                        0.1,
-                    ))),
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(
+                        color_section_offset(SWATCH_SIZE).extend(0.1),
+                    )),
                     ..Default::default()
                 },
                 Fill::color(*color),
+                ColorSwatchColor,
                 Name::new("Swatch Color"),
             ));
 
             builder.spawn((
                 Text2dBundle {
                     text_2d_bounds: Text2dBounds {
-                        // Wrap text in the rectangle
-                        size: Vec2 {
-                            x: SWATCH_SIZE.x,
-                            y: SWATCH_SIZE.y / 2.,
-                        } * 0.8,
+                        size: text_bounds(SWATCH_SIZE),
                     },
                     text: Text {
                         sections: vec![TextSection::new(text, text_style.clone())],
@@ -119,7 +118,7 @@ pub(crate) fn spawn_swatch(
                         linebreak_behavior: BreakLineOn::WordBoundary,
                     },
                     // ensure the text is drawn on top of the box
-                    transform: Transform::from_translation(Vec3::new(0., -73., 0.1)),
+                    transform: Transform::from_translation(text_offset(SWATCH_SIZE).extend(0.1)),
                     ..default()
                 },
                 ColorSwatchText,
@@ -141,7 +140,73 @@ pub(crate) fn spawn_swatch(
                 PostItShadow,
                 Name::new("Swatch Shadow"),
             ));
+        })
+        .id()
+}
+
+/// The color section spans the top of the swatch, with the same margins at any size.
+fn color_section_size(size: Vec2) -> Vec2 {
+    size * SWATCH_COLOR_SECTION_SIZE / SWATCH_SIZE
+}
+
+fn color_section_offset(size: Vec2) -> Vec2 {
+    Vec2::new(0., (size.y / 2.) - (color_section_size(size).y / 2.) - 2.)
+}
+
+fn text_bounds(size: Vec2) -> Vec2 {
+    // Wrap text in the rectangle
+    Vec2::new(size.x, size.y / 2.) * 0.8
+}
+
+fn text_offset(size: Vec2) -> Vec2 {
+    Vec2::new(0., -73. * size.y / SWATCH_SIZE.y)
+}
+
+type SwatchColorFilter = (With<ColorSwatchColor>, Without<ItemSize>);
+
+type SwatchShadowFilter = (
+    With<PostItShadow>,
+    Without<ItemSize>,
+    Without<ColorSwatchColor>,
+);
+
+type SwatchTextFilter = (With<ColorSwatchText>, Without<ColorSwatchColor>);
+
+fn resize_swatches(
+    mut commands: Commands,
+    mut swatch_query: Query<ItemOutlineQuery, (With<ColorSwatch>, Changed<ItemSize>)>,
+    mut color_query: Query<(&mut Path, &mut Transform), SwatchColorFilter>,
+    mut shadow_query: Query<&mut Path, SwatchShadowFilter>,
+    mut text_query: Query<(&mut Text2dBounds, &mut Transform), SwatchTextFilter>,
+) {
+    for (entity, size, children, mut path) in &mut swatch_query {
+        let size = size.0;
+        *path = GeometryBuilder::build_as(&shapes::Rectangle {
+            extents: size,
+            ..Default::default()
         });
+        commands.entity(entity).insert(item_aabb(size));
+
+        for child in children {
+            if let Ok((mut color_path, mut transform)) = color_query.get_mut(*child) {
+                *color_path = GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: color_section_size(size),
+                    ..Default::default()
+                });
+                transform.translation = color_section_offset(size).extend(transform.translation.z);
+            }
+            if let Ok(mut shadow_path) = shadow_query.get_mut(*child) {
+                *shadow_path = GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: size,
+                    ..Default::default()
+                });
+            }
+            if let Ok((mut bounds, mut transform)) = text_query.get_mut(*child) {
+                bounds.size = text_bounds(size);
+                transform.translation = text_offset(size).extend(transform.translation.z);
+            }
+        }
+    }
 }
 
 fn on_theme_change(
@@ -162,20 +227,139 @@ fn on_theme_change(
 
 fn add_swatch(
     mut commands: Commands,
-    mut events: EventReader<AddItemEvent>,
+    mut events: EventReader<SpawnItemEvent>,
     theme: Res<Theme>,
     font_stack: Res<FontStack>,
 ) {
     for event in events.read() {
-        if let AddItemEvent::Swatch(_color) = event {
-            // let color = Color::hex(color).unwrap();
+        if let AddItemEvent::Swatch(color) = &event.item {
+            let color = parse_color(color)
+                .unwrap_or_else(|| *SWATCH_COLORS.choose(&mut rand::thread_rng()).unwrap());
+
             spawn_swatch(
                 &mut commands,
                 &theme,
                 &font_stack,
-                Vec3::new(0., 0., 0.0),
-                SWATCH_COLORS.choose(&mut rand::thread_rng()).unwrap(),
+                event.position.unwrap_or_default().extend(0.),
+                &color,
             );
         }
     }
 }
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)` and `rgba(r, g, b, a)`
+/// colors. Like in CSS, channels out of range are clamped to 0 to 255, and alpha to 0 to 1.
+pub(crate) fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+
+    if let Some(hex) = text.strip_prefix('#') {
+        return Color::hex(hex).ok();
+    }
+
+    let (name, arguments) = text.strip_suffix(')')?.split_once('(')?;
+    let arguments = arguments
+        .split(',')
+        .map(|argument| {
+            argument
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let channel = |value: &f32| value.clamp(0., 255.).round() as u8;
+
+    match (name.trim(), arguments.as_slice()) {
+        ("rgb", [r, g, b]) => Some(Color::rgb_u8(channel(r), channel(g), channel(b))),
+        ("rgba", [r, g, b, a]) => {
+            Some(Color::rgb_u8(channel(r), channel(g), channel(b)).with_a(a.clamp(0., 1.)))
+        }
+        _ => None,
+    }
+}
+
+/// Parses pasted text as a color, only for `#rrggbb`, `#rrggbbaa`, `rgb()` and `rgba()`.
+/// Shorter hex codes are left out, they are too easily ordinary words like `#add` or `#cafe`.
+pub(crate) fn parse_pasted_color(text: &str) -> Option<Color> {
+    match text.trim().strip_prefix('#') {
+        Some(hex) if !matches!(hex.len(), 6 | 8) => None,
+        _ => parse_color(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn red() -> Color {
+        Color::rgb_u8(255, 0, 0)
+    }
+
+    #[test]
+    fn hex_colors_of_every_length_are_parsed() {
+        for text in ["#f00", "#f00f", "#ff0000", "#ff0000ff", "#FF0000"] {
+            assert_eq!(parse_color(text), Some(red()), "{text}");
+        }
+        assert_eq!(parse_color("#ff000080"), Some(red().with_a(128. / 255.)));
+    }
+
+    #[test]
+    fn rgb_colors_are_clamped_to_their_range() {
+        assert_eq!(parse_color("rgb(255, 0, 0)"), Some(red()));
+        assert_eq!(parse_color("rgb(300, -20, 0.4)"), Some(red()));
+        assert_eq!(
+            parse_color("rgb(127.6, 0, 0)"),
+            Some(Color::rgb_u8(128, 0, 0))
+        );
+        assert_eq!(parse_color("rgba(255, 0, 0, 0.5)"), Some(red().with_a(0.5)));
+        assert_eq!(parse_color("rgba(255, 0, 0, 2)"), Some(red()));
+        assert_eq!(parse_color("rgba(255, 0, 0, -1)"), Some(red().with_a(0.)));
+    }
+
+    #[test]
+    fn whitespace_around_and_inside_colors_is_ignored() {
+        for text in [
+            "  #ff0000 ",
+            "\n#f00\t",
+            "rgb( 255 ,0,  0 )",
+            " rgb (255, 0, 0) ",
+        ] {
+            assert_eq!(parse_color(text), Some(red()), "{text:?}");
+        }
+        // But not inside a hex code
+        assert_eq!(parse_color("# ff0000"), None);
+    }
+
+    #[test]
+    fn malformed_colors_are_rejected() {
+        for text in [
+            "",
+            "#",
+            "#ff00000",
+            "#ggg",
+            "ff0000",
+            "rgb(255, 0)",
+            "rgb(255, 0, 0, 1)",
+            "rgba(255, 0, 0)",
+            "rgb(red, 0, 0)",
+            "rgb(NaN, 0, 0)",
+            "rgb(inf, 0, 0)",
+            "rgb(255, 0, 0",
+            "hsl(0, 100, 50)",
+        ] {
+            assert_eq!(parse_color(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn pasted_colors_need_a_full_hex_code() {
+        assert_eq!(parse_pasted_color("#ff0000"), Some(red()));
+        assert_eq!(parse_pasted_color(" #ff0000ff\n"), Some(red()));
+        assert_eq!(parse_pasted_color("rgb(255, 0, 0)"), Some(red()));
+
+        // Short codes are too often words, like hashtags
+        for text in ["#f00", "#add", "#cafe", "#bead"] {
+            assert_eq!(parse_pasted_color(text), None, "{text}");
+        }
+    }
+}
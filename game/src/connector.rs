@@ -0,0 +1,478 @@
+use bevy::{render::primitives::Aabb, transform::TransformSystem, utils::HashSet};
+use bevy_prototype_lyon::plugin::BuildShapes;
+
+use crate::{
+    document::FontDocument,
+    events::{ArrowHead, ConnectorAnchor, ConnectorEvent, ConnectorStyle},
+    history::History,
+    item::{item_aabb, update_item_index, Item, ItemBundle, ItemDeletion, ItemId, ItemIndex},
+    keymap::{Action, Keymap},
+    prelude::*,
+    rich_text::RichText,
+    select::components::Selected,
+    text_edit::{not_editing_text, TextEditor},
+    theme::{Theme, ThemeDidChange},
+    FontFamilyKind, FontStack, FontVariant,
+};
+
+const CONNECTOR_STROKE_WIDTH: f32 = 4.0;
+const ARROW_HEAD_SIZE: f32 = 28.0;
+/// How far curved connectors leave their items before bending towards the other one.
+const MIN_BEND: f32 = 80.0;
+/// Connectors stack above frames and below every other item.
+pub(crate) const CONNECTOR_Z: f32 = 0.5;
+pub(crate) const CONNECTOR_Z_STEP: f32 = 0.00001;
+
+pub struct ConnectorPlugin;
+
+impl Plugin for ConnectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                connector_shortcuts.run_if(not_editing_text),
+                apply_connector_events.after(connector_shortcuts),
+                delete_detached_connectors.in_set(ItemDeletion::CleanUp),
+                connector_theme_change,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            route_connectors
+                .after(TransformSystem::TransformPropagate)
+                .after(update_item_index)
+                .before(BuildShapes),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Connector {
+    pub from: ItemId,
+    pub to: ItemId,
+    pub from_anchor: ConnectorAnchor,
+    pub to_anchor: ConnectorAnchor,
+    pub style: ConnectorStyle,
+    /// Drawn where the connector meets `to`.
+    pub head: ArrowHead,
+}
+
+/// The points the connector was last drawn through, in its own space.
+/// Curved connectors have the start, two control points and the end.
+#[derive(Component, Default)]
+struct ConnectorRoute(Vec<Vec2>);
+
+#[derive(Component)]
+struct ConnectorHead;
+
+#[derive(Component)]
+pub(crate) struct ConnectorLabel;
+
+/// The font of connector labels.
+pub(crate) fn connector_label_font(font_stack: &FontStack) -> FontDocument {
+    FontDocument {
+        family: FontFamilyKind::Body,
+        variant: FontVariant::Regular,
+        size: font_stack.size.small,
+    }
+}
+
+pub(crate) fn spawn_connector(
+    commands: &mut Commands,
+    theme: &Theme,
+    font_stack: &FontStack,
+    position: Vec3,
+    connector: Connector,
+    label: RichText,
+) -> Entity {
+    commands
+        .spawn((
+            ItemBundle {
+                fill: Fill::color(Color::NONE),
+                stroke: Stroke::new(theme.connector_color, CONNECTOR_STROKE_WIDTH),
+                shape: ShapeBundle {
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            connector,
+            ConnectorRoute::default(),
+            // Routed once its items are found
+            item_aabb(Vec2::ZERO),
+            Name::new("Connector"),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                ShapeBundle {
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(Vec3::new(
+                        0.,
+                        0.,
+                        CONNECTOR_Z_STEP * 0.2,
+                    ))),
+                    ..Default::default()
+                },
+                Fill::color(head_fill(connector.head, theme)),
+                Stroke::new(theme.connector_color, CONNECTOR_STROKE_WIDTH),
+                ConnectorHead,
+                Name::new("Connector Head"),
+            ));
+
+            builder.spawn((
+                Text2dBundle {
+                    text: Text {
+                        sections: label.sections(font_stack, theme.default_text_color),
+                        alignment: TextAlignment::Center,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(
+                        0.,
+                        0.,
+                        CONNECTOR_Z_STEP * 0.4,
+                    )),
+                    ..default()
+                },
+                label,
+                ConnectorLabel,
+                Name::new("Connector Label"),
+            ));
+        })
+        .id()
+}
+
+fn head_fill(head: ArrowHead, theme: &Theme) -> Color {
+    match head {
+        ArrowHead::Filled => theme.connector_color,
+        ArrowHead::None | ArrowHead::Open => Color::NONE,
+    }
+}
+
+fn connector_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut connector_writer: EventWriter<ConnectorEvent>,
+) {
+    if keymap.just_pressed(Action::Connect, &keys) {
+        connector_writer.send(ConnectorEvent::Connect {
+            style: ConnectorStyle::default(),
+            head: ArrowHead::default(),
+        });
+    }
+}
+
+/// Selected items that can be connected, connectors can't.
+type ConnectableFilter = (With<Selected>, Without<Connector>);
+
+fn apply_connector_events(
+    mut commands: Commands,
+    mut events: EventReader<ConnectorEvent>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    selected_query: Query<(&ItemId, &GlobalTransform), ConnectableFilter>,
+    mut connector_query: Query<&mut Connector, With<Selected>>,
+) {
+    for event in events.read() {
+        match *event {
+            ConnectorEvent::Connect { style, head } => {
+                let mut items = selected_query.iter().collect::<Vec<_>>();
+                if items.len() != 2 {
+                    continue;
+                }
+                items.sort_by(|(_, a), (_, b)| a.translation().z.total_cmp(&b.translation().z));
+
+                spawn_connector(
+                    &mut commands,
+                    &theme,
+                    &font_stack,
+                    Vec3::ZERO,
+                    Connector {
+                        from: *items[0].0,
+                        to: *items[1].0,
+                        from_anchor: ConnectorAnchor::default(),
+                        to_anchor: ConnectorAnchor::default(),
+                        style,
+                        head,
+                    },
+                    RichText::plain("", connector_label_font(&font_stack)),
+                );
+            }
+            ConnectorEvent::SetStyle(style) => {
+                for mut connector in &mut connector_query {
+                    connector.style = style;
+                }
+            }
+            ConnectorEvent::SetHead(head) => {
+                for mut connector in &mut connector_query {
+                    connector.head = head;
+                }
+            }
+            ConnectorEvent::SetAnchors { from, to } => {
+                for mut connector in &mut connector_query {
+                    connector.from_anchor = from;
+                    connector.to_anchor = to;
+                }
+            }
+        }
+    }
+}
+
+/// Deletes connectors whose items were deleted.
+fn delete_detached_connectors(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Item>,
+    history: Res<History>,
+    item_query: Query<&ItemId, With<Item>>,
+    connector_query: Query<(Entity, &Connector)>,
+) {
+    // Undo restores connectors with their items as they were, and loading a board replaces
+    // items with new ones that keep their ids
+    if removed.read().count() == 0 || history.is_restoring() {
+        return;
+    }
+
+    let ids = item_query.iter().collect::<HashSet<_>>();
+    for (entity, connector) in &connector_query {
+        if !ids.contains(&connector.from) || !ids.contains(&connector.to) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+type ConnectorRouteQuery = (
+    Entity,
+    Ref<'static, Connector>,
+    &'static GlobalTransform,
+    &'static Children,
+    &'static mut ConnectorRoute,
+    &'static mut Path,
+);
+
+type EndpointFilter = (With<Item>, Without<Connector>);
+
+type ConnectorHeadFilter = (With<ConnectorHead>, Without<Connector>);
+
+/// Redraws connectors whose items moved, or that were restyled.
+fn route_connectors(
+    mut commands: Commands,
+    item_index: Res<ItemIndex>,
+    theme: Res<Theme>,
+    mut connector_query: Query<ConnectorRouteQuery>,
+    endpoint_query: Query<(&GlobalTransform, &Aabb), EndpointFilter>,
+    mut head_query: Query<(&mut Path, &mut Fill), ConnectorHeadFilter>,
+    mut label_query: Query<&mut Transform, With<ConnectorLabel>>,
+) {
+    for (entity, connector, transform, children, mut route, mut path) in &mut connector_query {
+        // There is no route from an item to itself, boards saved elsewhere may still have one
+        if connector.from == connector.to {
+            continue;
+        }
+        let endpoint = |id| {
+            item_index
+                .entity(id)
+                .and_then(|entity| endpoint_query.get(entity).ok())
+        };
+        let (Some(from), Some(to)) = (endpoint(connector.from), endpoint(connector.to)) else {
+            continue;
+        };
+
+        // Drawn in the connector's own space, so it stays attached however the connector moves
+        let to_local = transform.affine().inverse();
+        let points = route_points(&connector, from, to)
+            .into_iter()
+            .map(|point| to_local.transform_point3(point.extend(0.)).xy())
+            .collect::<Vec<_>>();
+        if route.0 == points && !connector.is_changed() {
+            continue;
+        }
+
+        *path = route_path(connector.style, &points);
+        let bounds = points
+            .iter()
+            .map(|point| Rect::from_corners(*point, *point))
+            .reduce(|rect, point| rect.union(point))
+            .unwrap_or_default()
+            .inset(ARROW_HEAD_SIZE);
+        commands.entity(entity).insert(Aabb::from_min_max(
+            bounds.min.extend(0.),
+            bounds.max.extend(0.),
+        ));
+
+        let tip = points[points.len() - 1];
+        let direction = (tip - points[points.len() - 2]).normalize_or_zero();
+        let middle = route_middle(connector.style, &points);
+        for child in children {
+            if let Ok((mut head_path, mut fill)) = head_query.get_mut(*child) {
+                *head_path = head_path_at(connector.head, tip, direction);
+                fill.color = head_fill(connector.head, &theme);
+            }
+            if let Ok(mut transform) = label_query.get_mut(*child) {
+                transform.translation = middle.extend(transform.translation.z);
+            }
+        }
+
+        route.0 = points;
+    }
+}
+
+type Endpoint<'a> = (&'a GlobalTransform, &'a Aabb);
+
+/// The points a connector is drawn through, on the canvas.
+fn route_points(connector: &Connector, from: Endpoint, to: Endpoint) -> Vec<Vec2> {
+    let (start, start_direction) = anchor_point(connector.from_anchor, from, center(to));
+    let (end, end_direction) = anchor_point(connector.to_anchor, to, center(from));
+
+    match connector.style {
+        ConnectorStyle::Straight => vec![start, end],
+        ConnectorStyle::Elbow => {
+            let horizontal = |direction: Vec2| direction.x.abs() >= direction.y.abs();
+            match (horizontal(start_direction), horizontal(end_direction)) {
+                (true, true) => {
+                    let x = (start.x + end.x) / 2.;
+                    vec![start, Vec2::new(x, start.y), Vec2::new(x, end.y), end]
+                }
+                (false, false) => {
+                    let y = (start.y + end.y) / 2.;
+                    vec![start, Vec2::new(start.x, y), Vec2::new(end.x, y), end]
+                }
+                (true, false) => vec![start, Vec2::new(end.x, start.y), end],
+                (false, true) => vec![start, Vec2::new(start.x, end.y), end],
+            }
+        }
+        ConnectorStyle::Curved => {
+            let bend = (start.distance(end) / 2.).max(MIN_BEND);
+            vec![
+                start,
+                start + start_direction * bend,
+                end + end_direction * bend,
+                end,
+            ]
+        }
+    }
+}
+
+fn center((transform, aabb): Endpoint) -> Vec2 {
+    transform.transform_point(aabb.center.into()).xy()
+}
+
+/// The point on an item's edge a connector is attached to, and the direction it leaves in.
+fn anchor_point(
+    anchor: ConnectorAnchor,
+    (transform, aabb): Endpoint,
+    toward: Vec2,
+) -> (Vec2, Vec2) {
+    let center = aabb.center.xy();
+    let half_extents = aabb.half_extents.xy();
+
+    let side = match anchor {
+        ConnectorAnchor::Auto => {
+            // Compared in the item's own space, so rotated items are left from the facing edge
+            let offset = transform
+                .affine()
+                .inverse()
+                .transform_point3(toward.extend(0.))
+                .xy()
+                - center;
+            let offset = offset / half_extents.max(Vec2::ONE);
+            if offset.x.abs() >= offset.y.abs() {
+                Vec2::new(offset.x.signum(), 0.)
+            } else {
+                Vec2::new(0., offset.y.signum())
+            }
+        }
+        ConnectorAnchor::Top => Vec2::Y,
+        ConnectorAnchor::Right => Vec2::X,
+        ConnectorAnchor::Bottom => Vec2::NEG_Y,
+        ConnectorAnchor::Left => Vec2::NEG_X,
+    };
+
+    let point = transform
+        .transform_point((center + side * half_extents).extend(0.))
+        .xy();
+    let direction = transform
+        .affine()
+        .transform_vector3(side.extend(0.))
+        .xy()
+        .normalize_or_zero();
+
+    (point, direction)
+}
+
+fn route_path(style: ConnectorStyle, points: &[Vec2]) -> Path {
+    let mut builder = PathBuilder::new();
+    builder.move_to(points[0]);
+    if style == ConnectorStyle::Curved {
+        builder.cubic_bezier_to(points[1], points[2], points[3]);
+    } else {
+        for point in &points[1..] {
+            builder.line_to(*point);
+        }
+    }
+
+    builder.build()
+}
+
+/// Where the label sits, halfway along the route.
+fn route_middle(style: ConnectorStyle, points: &[Vec2]) -> Vec2 {
+    if style == ConnectorStyle::Curved {
+        return (points[0] + points[1] * 3. + points[2] * 3. + points[3]) / 8.;
+    }
+
+    let mut remaining = points
+        .windows(2)
+        .map(|segment| segment[0].distance(segment[1]))
+        .sum::<f32>()
+        / 2.;
+    for segment in points.windows(2) {
+        let length = segment[0].distance(segment[1]);
+        if remaining <= length {
+            return segment[0].lerp(segment[1], remaining / length.max(f32::EPSILON));
+        }
+        remaining -= length;
+    }
+
+    points[0]
+}
+
+fn head_path_at(head: ArrowHead, tip: Vec2, direction: Vec2) -> Path {
+    let mut builder = PathBuilder::new();
+    if head != ArrowHead::None && direction != Vec2::ZERO {
+        let base = tip - direction * ARROW_HEAD_SIZE;
+        let side = direction.perp() * ARROW_HEAD_SIZE / 2.;
+        builder.move_to(base + side);
+        builder.line_to(tip);
+        builder.line_to(base - side);
+        if head == ArrowHead::Filled {
+            builder.close();
+        }
+    }
+
+    builder.build()
+}
+
+/// Editors render their own sections.
+type ConnectorLabelFilter = (With<ConnectorLabel>, Without<TextEditor>);
+
+fn connector_theme_change(
+    mut theme_event_reader: EventReader<ThemeDidChange>,
+    font_stack: Res<FontStack>,
+    mut connector_query: Query<(&Connector, &Children, &mut Stroke)>,
+    mut head_query: Query<(&mut Stroke, &mut Fill), ConnectorHeadFilter>,
+    mut label_query: Query<(&RichText, &mut Text), ConnectorLabelFilter>,
+) {
+    for event in theme_event_reader.read() {
+        let theme = &event.theme;
+
+        for (connector, children, mut stroke) in &mut connector_query {
+            stroke.color = theme.connector_color;
+            for child in children {
+                if let Ok((mut stroke, mut fill)) = head_query.get_mut(*child) {
+                    stroke.color = theme.connector_color;
+                    fill.color = head_fill(connector.head, theme);
+                }
+            }
+        }
+        for (rich_text, mut text) in &mut label_query {
+            text.sections = rich_text.sections(&font_stack, theme.default_text_color);
+        }
+    }
+}
@@ -0,0 +1,1199 @@
+use bevy::{text::Text2dBounds, utils::HashMap};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_swatch::{spawn_swatch, ColorSwatch, ColorSwatchColor, SWATCH_COLORS},
+    connector::{connector_label_font, spawn_connector, Connector, ConnectorLabel},
+    drawing::{spawn_pen_stroke, PenStroke},
+    events::{
+        ArrowHead, ConnectorAnchor, ConnectorStyle, LoadBoardEvent, OutputEvent, ShapeKind,
+        TxOutputEvent,
+    },
+    frame::{frame_title_font, spawn_frame, Frame, FrameContents, FrameTitle},
+    group::{spawn_group, Group},
+    item::{Item, ItemId, ItemSize},
+    post_it::{
+        post_it_font, spawn_image, spawn_post_it, ImageItem, ImageSprite, PostIt, PostItText,
+    },
+    prelude::*,
+    rich_text::{RichText, TextSpan},
+    select::components::Selectable,
+    shape::{spawn_shape, ShapeItem},
+    text::{spawn_text, wrap_width, CanvasText, CanvasTextText},
+    text_edit::TextEditor,
+    theme::Theme,
+    FontFamilyKind, FontStack, FontVariant, ImageCache,
+};
+
+const SNAPSHOT_INTERVAL_SECONDS: f32 = 1.0;
+
+pub struct DocumentPlugin;
+
+impl Plugin for DocumentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoardSnapshotState>().add_systems(
+            Update,
+            (
+                load_board,
+                mark_board_changed.after(load_board),
+                send_board_snapshot.after(mark_board_changed),
+            ),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct BoardSnapshotState {
+    timer: Timer,
+    dirty: bool,
+}
+
+impl Default for BoardSnapshotState {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(SNAPSHOT_INTERVAL_SECONDS, TimerMode::Repeating),
+            dirty: false,
+        }
+    }
+}
+
+/// A serializable snapshot of every item on a board.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BoardDocument {
+    pub items: Vec<ItemDocument>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ItemDocument {
+    /// Boards saved before items had ids get new ones.
+    #[serde(default)]
+    pub id: ItemId,
+    pub position: Vec2,
+    /// Only the relative order matters, items are re-stacked when spawned.
+    pub z: f32,
+    /// The box size of post-its, swatches and images, when it isn't their default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Vec2>,
+    /// Counter-clockwise, in radians.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rotation: f32,
+    pub kind: ItemKind,
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl ItemDocument {
+    /// Whether the item can be spawned, connectors need two different items to run between.
+    fn is_spawnable(&self) -> bool {
+        match &self.kind {
+            ItemKind::Connector { from, to, .. } => from != to,
+            _ => true,
+        }
+    }
+
+    /// Gives the item, and the items in it, new ids, remembering the old ones in `renewed`.
+    fn renew_ids(&mut self, renewed: &mut HashMap<ItemId, ItemId>) {
+        let id = ItemId::new();
+        renewed.insert(std::mem::replace(&mut self.id, id), id);
+        if let ItemKind::Group { items } = &mut self.kind {
+            for item in items {
+                item.renew_ids(renewed);
+            }
+        }
+    }
+
+    /// How many stacking steps the item takes up, counting every item in it when it is a group.
+    fn stacked_size(&self) -> usize {
+        match &self.kind {
+            ItemKind::Group { items } => 1 + items.iter().map(Self::stacked_size).sum::<usize>(),
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ItemKind {
+    PostIt {
+        text: String,
+        /// Only styled text has spans, they take precedence over `text`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<TextSpan>,
+    },
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<TextSpan>,
+        font: FontDocument,
+        /// Text without a width grows to fit its lines.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        wrap_width: Option<f32>,
+    },
+    Swatch {
+        color: Color,
+    },
+    Image {
+        source: String,
+    },
+    /// Member positions are relative to the group.
+    Group {
+        items: Vec<ItemDocument>,
+    },
+    Frame {
+        title: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<TextSpan>,
+        /// Collapsed frames hide the items they hold.
+        #[serde(default, skip_serializing_if = "is_false")]
+        collapsed: bool,
+        /// The items the frame holds, they move along with it.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        items: Vec<ItemId>,
+    },
+    /// A line or arrow from one item to another, routed between them when spawned.
+    Connector {
+        from: ItemId,
+        to: ItemId,
+        #[serde(default)]
+        from_anchor: ConnectorAnchor,
+        #[serde(default)]
+        to_anchor: ConnectorAnchor,
+        #[serde(default)]
+        style: ConnectorStyle,
+        #[serde(default)]
+        head: ArrowHead,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        label: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<TextSpan>,
+    },
+    /// A pen or highlighter stroke, through points relative to the item.
+    Drawing {
+        points: Vec<Vec2>,
+        color: Color,
+        width: f32,
+        #[serde(default, skip_serializing_if = "is_false")]
+        highlighter: bool,
+    },
+    /// A vector shape, drawn to fill the item's size.
+    Shape {
+        shape: ShapeKind,
+        fill: Color,
+        stroke: Color,
+        stroke_width: f32,
+        #[serde(default)]
+        corner_radius: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FontDocument {
+    pub family: FontFamilyKind,
+    pub variant: FontVariant,
+    pub size: f32,
+}
+
+impl BoardDocument {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// The example board shown when nothing has been saved yet.
+    pub(crate) fn demo(font_stack: &FontStack) -> Self {
+        let mut swatch_colors = SWATCH_COLORS.choose_multiple(&mut rand::thread_rng(), 4);
+
+        let item = |x: f32, y: f32, kind: ItemKind| ItemDocument {
+            id: ItemId::new(),
+            position: Vec2::new(x, y),
+            z: 0.,
+            size: None,
+            rotation: 0.,
+            kind,
+        };
+
+        Self {
+            items: vec![
+                item(
+                    -500.,
+                    200.,
+                    ItemKind::PostIt {
+                        text: "This is a Post-It. \n\nYou can add more by clicking the chat bubble icon above.".to_string(),
+                        spans: Vec::new(),
+                    },
+                ),
+                item(
+                    -64.,
+                    -87.,
+                    ItemKind::PostIt {
+                        text: "You can also add color swatches and text boxes.".to_string(),
+                        spans: Vec::new(),
+                    },
+                ),
+                item(
+                    -150.,
+                    283.,
+                    ItemKind::Swatch {
+                        color: *swatch_colors.next().unwrap(),
+                    },
+                ),
+                item(
+                    286.,
+                    -2.,
+                    ItemKind::Swatch {
+                        color: *swatch_colors.next().unwrap(),
+                    },
+                ),
+                item(
+                    535.,
+                    -2.,
+                    ItemKind::Swatch {
+                        color: *swatch_colors.next().unwrap(),
+                    },
+                ),
+                item(
+                    -408.,
+                    -170.,
+                    ItemKind::Swatch {
+                        color: *swatch_colors.next().unwrap(),
+                    },
+                ),
+                item(
+                    400.,
+                    370.,
+                    ItemKind::Text {
+                        text: "An Example Moodboard".to_string(),
+                        spans: Vec::new(),
+                        font: FontDocument {
+                            family: FontFamilyKind::Title,
+                            variant: FontVariant::Bold,
+                            size: font_stack.size.xlarge,
+                        },
+                        wrap_width: None,
+                    },
+                ),
+                item(
+                    257.,
+                    280.,
+                    ItemKind::Text {
+                        text: "Try dragging things around!".to_string(),
+                        spans: Vec::new(),
+                        font: FontDocument {
+                            family: FontFamilyKind::Body,
+                            variant: FontVariant::Italic,
+                            size: font_stack.size.large,
+                        },
+                        wrap_width: None,
+                    },
+                ),
+                item(
+                    576.,
+                    -424.,
+                    ItemKind::Image {
+                        source: "images/night_lights.jpg".to_string(),
+                    },
+                ),
+            ],
+        }
+    }
+
+    /// Gives every item new ids, for copies. Frames keep the copies of their items and
+    /// forget the items that weren't copied, connectors are only copied with both their items.
+    pub(crate) fn renew_ids(&mut self) {
+        let mut renewed = HashMap::new();
+        for item in &mut self.items {
+            item.renew_ids(&mut renewed);
+        }
+
+        self.items.retain(|item| match &item.kind {
+            ItemKind::Connector { from, to, .. } => {
+                renewed.contains_key(from) && renewed.contains_key(to)
+            }
+            _ => true,
+        });
+
+        let mut pending = self.items.iter_mut().collect::<Vec<_>>();
+        while let Some(item) = pending.pop() {
+            match &mut item.kind {
+                ItemKind::Group { items } => pending.extend(items),
+                ItemKind::Frame { items, .. } => {
+                    *items = items
+                        .iter()
+                        .filter_map(|id| renewed.get(id).copied())
+                        .collect();
+                }
+                ItemKind::Connector { from, to, .. } => {
+                    // Connectors in groups keep pointing at the original items
+                    *from = renewed.get(from).copied().unwrap_or(*from);
+                    *to = renewed.get(to).copied().unwrap_or(*to);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// A copy with new ids and every item moved by `offset`, so the copies keep their layout.
+    pub(crate) fn pasted(&self, offset: Vec2) -> Self {
+        let mut board = self.clone();
+        board.renew_ids();
+        for item in &mut board.items {
+            item.position += offset;
+        }
+
+        board
+    }
+
+    /// Every item on the board, including the items in groups.
+    pub fn all_items(&self) -> Vec<&ItemDocument> {
+        let mut items = Vec::new();
+        let mut pending = self.items.iter().collect::<Vec<_>>();
+        while let Some(item) = pending.pop() {
+            if let ItemKind::Group { items: members } = &item.kind {
+                pending.extend(members);
+            }
+            items.push(item);
+        }
+
+        items
+    }
+
+    pub(crate) fn capture(world: &World) -> Self {
+        Self::capture_where(world, |_| true)
+    }
+
+    /// Captures only the items `filter` accepts.
+    /// Members of captured groups are captured with their group, other members on their own.
+    pub(crate) fn capture_where(world: &World, filter: impl Fn(&EntityRef) -> bool) -> Self {
+        let captured = |entity: &EntityRef| entity.contains::<Item>() && filter(entity);
+        let items = world
+            .iter_entities()
+            .filter(|entity| {
+                captured(entity)
+                    && !entity
+                        .get::<Parent>()
+                        .is_some_and(|parent| captured(&world.entity(parent.get())))
+            })
+            .filter_map(|entity| capture_item(world, entity, canvas_transform(world, entity)?))
+            .collect();
+
+        Self {
+            items: restack(items),
+        }
+    }
+
+    /// Spawns every item and returns their entities, along with the items they were spawned
+    /// from. Items that can't be spawned, like connectors from an item to itself, are skipped.
+    pub(crate) fn spawn(
+        &self,
+        commands: &mut Commands,
+        theme: &Theme,
+        font_stack: &FontStack,
+        asset_server: &AssetServer,
+        image_cache: &mut ResMut<ImageCache>,
+    ) -> Vec<(Entity, &ItemDocument)> {
+        self.items
+            .iter()
+            .filter(|item| item.is_spawnable())
+            .map(|item| {
+                // `ItemCounter` re-stacks new items in the order of their spawn z
+                let entity = spawn_item(
+                    item,
+                    item.z,
+                    commands,
+                    theme,
+                    font_stack,
+                    asset_server,
+                    image_cache,
+                );
+                (entity, item)
+            })
+            .collect()
+    }
+}
+
+/// Spawns an item at `z`, along with the items in it when it is a group.
+fn spawn_item(
+    item: &ItemDocument,
+    z: f32,
+    commands: &mut Commands,
+    theme: &Theme,
+    font_stack: &FontStack,
+    asset_server: &AssetServer,
+    image_cache: &mut ResMut<ImageCache>,
+) -> Entity {
+    let position = item.position.extend(z);
+
+    let entity = match &item.kind {
+        ItemKind::PostIt { text, spans } => spawn_post_it(
+            commands,
+            theme,
+            font_stack,
+            position,
+            rich_text(text, spans, post_it_font(font_stack)),
+        ),
+        ItemKind::Text {
+            text,
+            spans,
+            font,
+            wrap_width,
+        } => spawn_text(
+            commands,
+            theme,
+            font_stack,
+            position,
+            rich_text(text, spans, *font),
+            *wrap_width,
+        ),
+        ItemKind::Swatch { color } => spawn_swatch(commands, theme, font_stack, position, color),
+        ItemKind::Image { source } => {
+            let image = asset_server.load(source.clone());
+            spawn_image(commands, theme, position, image, image_cache)
+        }
+        ItemKind::Group { items } => {
+            let group = spawn_group(commands, position);
+
+            // Members are stacked above the group, in their order
+            let mut z = 1.;
+            let mut members = Vec::with_capacity(items.len());
+            for member in items.iter().filter(|member| member.is_spawnable()) {
+                let entity = spawn_item(
+                    member,
+                    z,
+                    commands,
+                    theme,
+                    font_stack,
+                    asset_server,
+                    image_cache,
+                );
+                commands.entity(entity).remove::<Selectable>();
+                members.push(entity);
+                z += member.stacked_size() as f32;
+            }
+            commands.entity(group).push_children(&members);
+
+            group
+        }
+        ItemKind::Frame {
+            title,
+            spans,
+            collapsed,
+            items,
+        } => spawn_frame(
+            commands,
+            theme,
+            font_stack,
+            position,
+            rich_text(title, spans, frame_title_font(font_stack)),
+            Frame {
+                collapsed: *collapsed,
+            },
+            FrameContents(items.clone()),
+        ),
+        ItemKind::Connector {
+            from,
+            to,
+            from_anchor,
+            to_anchor,
+            style,
+            head,
+            label,
+            spans,
+        } => spawn_connector(
+            commands,
+            theme,
+            font_stack,
+            position,
+            Connector {
+                from: *from,
+                to: *to,
+                from_anchor: *from_anchor,
+                to_anchor: *to_anchor,
+                style: *style,
+                head: *head,
+            },
+            rich_text(label, spans, connector_label_font(font_stack)),
+        ),
+        ItemKind::Drawing {
+            points,
+            color,
+            width,
+            highlighter,
+        } => spawn_pen_stroke(
+            commands,
+            position,
+            PenStroke {
+                points: points.clone(),
+                color: *color,
+                width: *width,
+                highlighter: *highlighter,
+            },
+        ),
+        ItemKind::Shape {
+            shape,
+            fill,
+            stroke,
+            stroke_width,
+            corner_radius,
+        } => spawn_shape(
+            commands,
+            position,
+            item.size.unwrap_or_default(),
+            ShapeItem {
+                kind: *shape,
+                fill: *fill,
+                stroke: *stroke,
+                stroke_width: *stroke_width,
+                corner_radius: *corner_radius,
+            },
+        ),
+    };
+    commands.entity(entity).insert(item.id);
+    if let Some(size) = item.size {
+        commands.entity(entity).insert(ItemSize(size));
+    }
+    if item.rotation != 0. {
+        commands.entity(entity).insert(
+            Transform::from_translation(position)
+                .with_rotation(Quat::from_rotation_z(item.rotation)),
+        );
+    }
+
+    entity
+}
+
+fn load_board(
+    mut commands: Commands,
+    mut events: EventReader<LoadBoardEvent>,
+    item_query: Query<Entity, (With<Item>, Without<Parent>)>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    asset_server: Res<AssetServer>,
+    mut image_cache: ResMut<ImageCache>,
+) {
+    // Only the latest board matters if several loads arrive in the same frame
+    let Some(event) = events.read().last() else {
+        return;
+    };
+
+    for entity in &item_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    image_cache.images.clear();
+
+    let board = event
+        .board
+        .clone()
+        .unwrap_or_else(|| BoardDocument::demo(&font_stack));
+
+    board.spawn(
+        &mut commands,
+        &theme,
+        &font_stack,
+        &asset_server,
+        &mut image_cache,
+    );
+}
+
+type ItemPlacedFilter = (With<Item>, Or<(Added<Item>, Changed<Transform>)>);
+
+/// Items changed in ways of their own, besides their text.
+type ItemRestyledFilter = Or<(
+    Changed<Frame>,
+    Changed<FrameContents>,
+    Changed<Connector>,
+    Changed<ShapeItem>,
+)>;
+
+fn mark_board_changed(
+    mut snapshot_state: ResMut<BoardSnapshotState>,
+    changed_query: Query<(), ItemPlacedFilter>,
+    text_query: Query<(), Changed<RichText>>,
+    frame_query: Query<(), ItemRestyledFilter>,
+    removed: RemovedComponents<Item>,
+) {
+    if !changed_query.is_empty()
+        || !text_query.is_empty()
+        || !frame_query.is_empty()
+        || !removed.is_empty()
+    {
+        snapshot_state.dirty = true;
+    }
+}
+
+fn send_board_snapshot(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    let mut snapshot_state = world.resource_mut::<BoardSnapshotState>();
+    snapshot_state.timer.tick(delta);
+
+    if !snapshot_state.timer.just_finished() || !snapshot_state.dirty {
+        return;
+    }
+    snapshot_state.dirty = false;
+
+    let board = BoardDocument::capture(world);
+    if let Some(tx_output_event) = world.get_resource::<TxOutputEvent>() {
+        tx_output_event.emit(OutputEvent::BoardSnapshot(board));
+    }
+}
+
+/// Captures an item at `transform`, which is relative to whatever holds the item in the capture.
+fn capture_item(world: &World, entity: EntityRef, transform: Transform) -> Option<ItemDocument> {
+    Some(ItemDocument {
+        id: *entity.get::<ItemId>()?,
+        position: transform.translation.xy(),
+        z: transform.translation.z,
+        size: entity.get::<ItemSize>().map(|size| size.0),
+        rotation: transform.rotation.to_euler(EulerRot::ZYX).0,
+        kind: capture_kind(world, entity)?,
+    })
+}
+
+/// Sorts items by their z and keeps only the stacking order, so captures of the same board
+/// compare equal.
+fn restack(mut items: Vec<ItemDocument>) -> Vec<ItemDocument> {
+    items.sort_by(|a, b| a.z.total_cmp(&b.z));
+    for (index, item) in items.iter_mut().enumerate() {
+        item.z = index as f32;
+    }
+
+    items
+}
+
+/// An item's transform on the canvas, including the transforms of the groups it is in.
+fn canvas_transform(world: &World, entity: EntityRef) -> Option<Transform> {
+    let transform = *entity.get::<Transform>()?;
+
+    match entity.get::<Parent>() {
+        Some(parent) => {
+            Some(canvas_transform(world, world.entity(parent.get()))?.mul_transform(transform))
+        }
+        None => Some(transform),
+    }
+}
+
+fn capture_kind(world: &World, entity: EntityRef) -> Option<ItemKind> {
+    if entity.contains::<PostIt>() {
+        let (text, spans) = capture_text(find_child::<PostItText>(world, entity)?)?;
+
+        Some(ItemKind::PostIt { text, spans })
+    } else if entity.contains::<CanvasText>() {
+        let text_entity = find_child::<CanvasTextText>(world, entity)?;
+        let font = text_entity.get::<RichText>()?.font;
+        let wrap_width = wrap_width(text_entity.get::<Text2dBounds>()?);
+        let (text, spans) = capture_text(text_entity)?;
+
+        Some(ItemKind::Text {
+            text,
+            spans,
+            font,
+            wrap_width,
+        })
+    } else if entity.contains::<ColorSwatch>() {
+        let fill = find_child::<ColorSwatchColor>(world, entity)?.get::<Fill>()?;
+
+        Some(ItemKind::Swatch { color: fill.color })
+    } else if entity.contains::<ImageItem>() {
+        let image = find_child::<ImageSprite>(world, entity)?.get::<Handle<Image>>()?;
+
+        Some(ItemKind::Image {
+            source: image.path()?.to_string(),
+        })
+    } else if entity.contains::<Group>() {
+        let items = entity
+            .get::<Children>()?
+            .iter()
+            .map(|child| world.entity(*child))
+            .filter(|child| child.contains::<Item>())
+            .filter_map(|child| capture_item(world, child, *child.get::<Transform>()?))
+            .collect();
+
+        Some(ItemKind::Group {
+            items: restack(items),
+        })
+    } else if let Some(frame) = entity.get::<Frame>() {
+        let (title, spans) = capture_text(find_child::<FrameTitle>(world, entity)?)?;
+
+        Some(ItemKind::Frame {
+            title,
+            spans,
+            collapsed: frame.collapsed,
+            items: entity.get::<FrameContents>()?.0.clone(),
+        })
+    } else if let Some(connector) = entity.get::<Connector>() {
+        let (label, spans) = capture_text(find_child::<ConnectorLabel>(world, entity)?)?;
+
+        Some(ItemKind::Connector {
+            from: connector.from,
+            to: connector.to,
+            from_anchor: connector.from_anchor,
+            to_anchor: connector.to_anchor,
+            style: connector.style,
+            head: connector.head,
+            label,
+            spans,
+        })
+    } else if let Some(stroke) = entity.get::<PenStroke>() {
+        Some(ItemKind::Drawing {
+            points: stroke.points.clone(),
+            color: stroke.color,
+            width: stroke.width,
+            highlighter: stroke.highlighter,
+        })
+    } else if let Some(shape) = entity.get::<ShapeItem>() {
+        Some(ItemKind::Shape {
+            shape: shape.kind,
+            fill: shape.fill,
+            stroke: shape.stroke,
+            stroke_width: shape.stroke_width,
+            corner_radius: shape.corner_radius,
+        })
+    } else {
+        None
+    }
+}
+
+fn find_child<'w, T: Component>(world: &'w World, entity: EntityRef) -> Option<EntityRef<'w>> {
+    entity
+        .get::<Children>()?
+        .iter()
+        .map(|child| world.entity(*child))
+        .find(|child| child.contains::<T>())
+}
+
+/// The text and spans of a text entity, including edits that haven't been committed yet.
+fn capture_text(entity: EntityRef) -> Option<(String, Vec<TextSpan>)> {
+    let rich_text = match entity.get::<TextEditor>() {
+        Some(editor) => editor.rich_text(),
+        None => entity.get::<RichText>()?,
+    };
+    let spans = if rich_text.is_plain() {
+        Vec::new()
+    } else {
+        rich_text.spans.clone()
+    };
+
+    Some((rich_text.text(), spans))
+}
+
+fn rich_text(text: &str, spans: &[TextSpan], font: FontDocument) -> RichText {
+    if spans.is_empty() {
+        RichText::plain(text, font)
+    } else {
+        RichText::new(spans.to_vec(), font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, utils::HashSet};
+
+    use super::*;
+    use crate::{rich_text::SpanStyle, theme::ThemePlugin, FontSize};
+
+    /// Positions and rotations go through transforms when spawned and captured.
+    const EPSILON: f32 = 1e-4;
+
+    fn item(x: f32, y: f32, z: f32, kind: ItemKind) -> ItemDocument {
+        ItemDocument {
+            id: ItemId::new(),
+            position: Vec2::new(x, y),
+            z,
+            size: None,
+            rotation: 0.,
+            kind,
+        }
+    }
+
+    fn font() -> FontDocument {
+        FontDocument {
+            family: FontFamilyKind::Title,
+            variant: FontVariant::Bold,
+            size: 32.,
+        }
+    }
+
+    fn styled_spans() -> Vec<TextSpan> {
+        vec![
+            TextSpan {
+                text: "Styled ".to_string(),
+                style: SpanStyle {
+                    bold: true,
+                    size: Some(FontSize::Large),
+                    ..Default::default()
+                },
+            },
+            TextSpan {
+                text: "text".to_string(),
+                style: SpanStyle {
+                    italic: true,
+                    color: Some(Palette::SKY_700),
+                    ..Default::default()
+                },
+            },
+        ]
+    }
+
+    /// A board with every kind of item, including a group, a frame holding items and a
+    /// connector between them.
+    fn board() -> BoardDocument {
+        // Sized, so the sizes items are spawned with are replaced by the saved ones
+        let post_it = ItemDocument {
+            size: Some(Vec2::new(250., 180.)),
+            rotation: 0.3,
+            ..item(
+                -200.,
+                100.,
+                0.,
+                ItemKind::PostIt {
+                    text: "A post-it".to_string(),
+                    spans: Vec::new(),
+                },
+            )
+        };
+        let swatch = ItemDocument {
+            size: Some(Vec2::new(120., 120.)),
+            ..item(
+                200.,
+                100.,
+                1.,
+                ItemKind::Swatch {
+                    color: Palette::SKY_200,
+                },
+            )
+        };
+        let connector = item(
+            0.,
+            0.,
+            2.,
+            ItemKind::Connector {
+                from: post_it.id,
+                to: swatch.id,
+                from_anchor: ConnectorAnchor::Right,
+                to_anchor: ConnectorAnchor::Left,
+                style: ConnectorStyle::Elbow,
+                head: ArrowHead::Filled,
+                label: String::new(),
+                spans: Vec::new(),
+            },
+        );
+        let frame = ItemDocument {
+            size: Some(Vec2::new(800., 500.)),
+            ..item(
+                0.,
+                0.,
+                3.,
+                ItemKind::Frame {
+                    title: "A frame".to_string(),
+                    spans: Vec::new(),
+                    collapsed: false,
+                    items: vec![post_it.id, swatch.id],
+                },
+            )
+        };
+        let group = item(
+            600.,
+            -400.,
+            4.,
+            ItemKind::Group {
+                items: vec![
+                    ItemDocument {
+                        rotation: -0.25,
+                        ..item(
+                            -50.,
+                            0.,
+                            0.,
+                            ItemKind::Text {
+                                text: "Styled text".to_string(),
+                                spans: styled_spans(),
+                                font: font(),
+                                wrap_width: Some(300.),
+                            },
+                        )
+                    },
+                    ItemDocument {
+                        size: Some(Vec2::new(120., 80.)),
+                        ..item(
+                            50.,
+                            0.,
+                            1.,
+                            ItemKind::Shape {
+                                shape: ShapeKind::Star { points: 5 },
+                                fill: Palette::SKY_200,
+                                stroke: Palette::SKY_700,
+                                stroke_width: 4.,
+                                corner_radius: 8.,
+                            },
+                        )
+                    },
+                ],
+            },
+        );
+
+        BoardDocument {
+            items: vec![
+                post_it,
+                swatch,
+                connector,
+                frame,
+                group,
+                item(
+                    -600.,
+                    400.,
+                    5.,
+                    ItemKind::Text {
+                        text: "A title".to_string(),
+                        spans: Vec::new(),
+                        font: font(),
+                        wrap_width: None,
+                    },
+                ),
+                ItemDocument {
+                    size: Some(Vec2::new(400., 300.)),
+                    rotation: -1.,
+                    ..item(
+                        600.,
+                        400.,
+                        6.,
+                        ItemKind::Image {
+                            source: "images/night_lights.jpg".to_string(),
+                        },
+                    )
+                },
+                item(
+                    -600.,
+                    -400.,
+                    7.,
+                    ItemKind::Drawing {
+                        points: vec![Vec2::ZERO, Vec2::new(10., 20.), Vec2::new(40., 10.)],
+                        color: Palette::SKY_700,
+                        width: 12.,
+                        highlighter: true,
+                    },
+                ),
+                ItemDocument {
+                    size: Some(Vec2::new(300., 0.)),
+                    ..item(
+                        0.,
+                        -600.,
+                        8.,
+                        ItemKind::Shape {
+                            shape: ShapeKind::Line,
+                            fill: Color::NONE,
+                            stroke: Palette::SKY_700,
+                            stroke_width: 2.,
+                            corner_radius: 0.,
+                        },
+                    )
+                },
+            ],
+        }
+    }
+
+    /// Spawns the board in a headless app, and captures it back.
+    fn spawn_and_capture(board: BoardDocument) -> BoardDocument {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), ThemePlugin))
+            .init_asset::<Image>()
+            .init_resource::<FontStack>()
+            .init_resource::<ImageCache>();
+
+        app.world.run_system_once(
+            move |mut commands: Commands,
+                  theme: Res<Theme>,
+                  font_stack: Res<FontStack>,
+                  asset_server: Res<AssetServer>,
+                  mut image_cache: ResMut<ImageCache>| {
+                board.spawn(
+                    &mut commands,
+                    &theme,
+                    &font_stack,
+                    &asset_server,
+                    &mut image_cache,
+                );
+            },
+        );
+
+        BoardDocument::capture(&app.world)
+    }
+
+    /// Compares every field, with room for float error in positions and rotations.
+    /// Groups are compared through their members, which are compared on their own.
+    fn assert_item_matches(actual: &ItemDocument, expected: &ItemDocument) {
+        assert_eq!(actual.id, expected.id);
+        assert!(
+            actual.position.abs_diff_eq(expected.position, EPSILON),
+            "{actual:#?} is not at {:?}",
+            expected.position
+        );
+        assert_eq!(actual.z, expected.z, "{actual:#?}");
+        assert_eq!(actual.size, expected.size, "{actual:#?}");
+        assert!(
+            (actual.rotation - expected.rotation).abs() < EPSILON,
+            "{actual:#?} is not rotated by {}",
+            expected.rotation
+        );
+
+        match (&actual.kind, &expected.kind) {
+            (ItemKind::Group { items: actual }, ItemKind::Group { items: expected }) => {
+                let ids =
+                    |items: &[ItemDocument]| items.iter().map(|item| item.id).collect::<Vec<_>>();
+                assert_eq!(ids(actual), ids(expected));
+            }
+            (actual, expected) => assert_eq!(actual, expected),
+        }
+    }
+
+    fn assert_boards_match(actual: &BoardDocument, expected: &BoardDocument) {
+        let ids = |board: &BoardDocument| {
+            board
+                .all_items()
+                .into_iter()
+                .map(|item| item.id)
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(ids(actual), ids(expected));
+
+        for item in expected.all_items() {
+            let actual_item = actual
+                .all_items()
+                .into_iter()
+                .find(|actual_item| actual_item.id == item.id)
+                .unwrap();
+            assert_item_matches(actual_item, item);
+        }
+    }
+
+    #[test]
+    fn every_item_kind_round_trips_through_json() {
+        let board = board();
+
+        for item in board.all_items() {
+            let json = serde_json::to_string(item).unwrap();
+            let parsed = serde_json::from_str::<ItemDocument>(&json).unwrap();
+            assert_eq!(&parsed, item, "{json}");
+        }
+
+        let json = board.to_json().unwrap();
+        assert_eq!(BoardDocument::from_json(&json).unwrap(), board);
+    }
+
+    #[test]
+    fn captured_items_are_spawned_as_they_were() {
+        let board = board();
+        let captured = spawn_and_capture(board.clone());
+        assert_boards_match(&captured, &board);
+
+        assert_boards_match(&spawn_and_capture(captured.clone()), &captured);
+    }
+
+    #[test]
+    fn connectors_from_an_item_to_itself_are_not_spawned() {
+        let mut board = board();
+        let post_it = board.items[0].id;
+        board.items.push(item(
+            0.,
+            0.,
+            9.,
+            ItemKind::Connector {
+                from: post_it,
+                to: post_it,
+                from_anchor: ConnectorAnchor::Auto,
+                to_anchor: ConnectorAnchor::Auto,
+                style: ConnectorStyle::Straight,
+                head: ArrowHead::Open,
+                label: String::new(),
+                spans: Vec::new(),
+            },
+        ));
+        let looped = board.items[board.items.len() - 1].id;
+
+        let captured = spawn_and_capture(board);
+        assert!(captured.all_items().iter().all(|item| item.id != looped));
+    }
+
+    #[test]
+    fn renewed_ids_are_new_and_kept_by_frames_and_connectors() {
+        let board = board();
+        let mut copy = board.clone();
+        copy.renew_ids();
+
+        let ids = |board: &BoardDocument| {
+            board
+                .all_items()
+                .into_iter()
+                .map(|item| item.id)
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(ids(&copy).len(), ids(&board).len());
+        assert!(ids(&copy).is_disjoint(&ids(&board)));
+
+        let (post_it, swatch) = (copy.items[0].id, copy.items[1].id);
+        assert!(matches!(
+            &copy.items[2].kind,
+            ItemKind::Connector { from, to, .. } if *from == post_it && *to == swatch
+        ));
+        assert!(matches!(
+            &copy.items[3].kind,
+            ItemKind::Frame { items, .. } if *items == [post_it, swatch]
+        ));
+    }
+
+    #[test]
+    fn copies_leave_out_what_was_not_copied_along() {
+        let board = board();
+        // The swatch, the connector from the post-it and the frame holding both, without the
+        // post-it
+        let mut copy = BoardDocument {
+            items: board.items[1..4].to_vec(),
+        };
+        copy.renew_ids();
+
+        assert_eq!(copy.items.len(), 2);
+        let swatch = copy.items[0].id;
+        assert!(matches!(
+            &copy.items[1].kind,
+            ItemKind::Frame { items, .. } if *items == [swatch]
+        ));
+    }
+
+    #[test]
+    fn pasted_items_keep_their_layout() {
+        let board = board();
+        let offset = Vec2::new(1000., -500.);
+        let pasted = spawn_and_capture(board.pasted(offset));
+
+        assert_eq!(pasted.items.len(), board.items.len());
+        for (pasted, item) in pasted.items.iter().zip(&board.items) {
+            assert_ne!(pasted.id, item.id);
+            assert!(
+                pasted.position.abs_diff_eq(item.position + offset, EPSILON),
+                "{pasted:#?} is not at {:?}",
+                item.position + offset
+            );
+            assert_eq!(pasted.size, item.size);
+            assert!((pasted.rotation - item.rotation).abs() < EPSILON);
+        }
+
+        // Group members stay where they were in their group
+        let member_positions = |board: &BoardDocument| -> Vec<Vec2> {
+            match &board.items[4].kind {
+                ItemKind::Group { items } => items.iter().map(|item| item.position).collect(),
+                kind => panic!("{kind:?} is not a group"),
+            }
+        };
+        let (pasted_members, members) = (member_positions(&pasted), member_positions(&board));
+        for (pasted, member) in pasted_members.iter().zip(&members) {
+            assert!(pasted.abs_diff_eq(*member, EPSILON));
+        }
+    }
+}
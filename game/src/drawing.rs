@@ -0,0 +1,332 @@
+use bevy::render::primitives::Aabb;
+
+use crate::{
+    canvas::CursorCoords,
+    color_swatch::parse_color,
+    events::{DrawingEvent, DrawingTool, OutputEvent, TxOutputEvent},
+    item::{ItemBundle, ItemDeletion},
+    keymap::{Action, Keymap},
+    prelude::*,
+    select::components::Selectable,
+    text_edit::not_editing_text,
+};
+
+const DEFAULT_COLOR: Color = Palette::RED_500;
+const DEFAULT_WIDTH: f32 = 6.0;
+/// Highlighter strokes are this much wider than pen strokes.
+const HIGHLIGHTER_WIDTH_SCALE: f32 = 4.0;
+const HIGHLIGHTER_ALPHA: f32 = 0.35;
+/// Points closer than this to the last one are dropped while drawing, so strokes stay light.
+const MIN_POINT_DISTANCE: f32 = 4.0;
+const ERASER_RADIUS: f32 = 12.0;
+/// Strokes in progress are drawn above everything but the selection.
+const DRAFT_Z: f32 = 998.0;
+
+pub struct DrawingPlugin;
+
+impl Plugin for DrawingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Drawing>().add_systems(
+            Update,
+            (
+                drawing_shortcuts.run_if(not_editing_text),
+                apply_drawing_events.after(drawing_shortcuts),
+                start_stroke.after(apply_drawing_events),
+                extend_stroke.after(start_stroke),
+                finish_stroke.after(extend_stroke),
+                erase_strokes
+                    .after(apply_drawing_events)
+                    .in_set(ItemDeletion::Delete),
+            ),
+        );
+    }
+}
+
+/// The picked drawing tool and what new strokes look like.
+#[derive(Resource)]
+pub(crate) struct Drawing {
+    tool: Option<DrawingTool>,
+    color: Color,
+    width: f32,
+}
+
+impl Default for Drawing {
+    fn default() -> Self {
+        Self {
+            tool: None,
+            color: DEFAULT_COLOR,
+            width: DEFAULT_WIDTH,
+        }
+    }
+}
+
+/// Dragging selects and moves items, no drawing tool is picked.
+pub(crate) fn not_drawing(drawing: Res<Drawing>) -> bool {
+    drawing.tool.is_none()
+}
+
+/// A finished stroke, through `points` relative to the item.
+#[derive(Component, Clone, Debug)]
+pub(crate) struct PenStroke {
+    pub points: Vec<Vec2>,
+    pub color: Color,
+    pub width: f32,
+    pub highlighter: bool,
+}
+
+/// The stroke being drawn, through points on the canvas.
+#[derive(Component)]
+struct DraftStroke(PenStroke);
+
+fn drawing_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    drawing: Res<Drawing>,
+    mut drawing_writer: EventWriter<DrawingEvent>,
+) {
+    for (action, tool) in [
+        (Action::Pen, DrawingTool::Pen),
+        (Action::Highlighter, DrawingTool::Highlighter),
+        (Action::Eraser, DrawingTool::Eraser),
+    ] {
+        if keymap.just_pressed(action, &keys) {
+            // Pressing the shortcut of the picked tool goes back to selecting
+            let tool = (drawing.tool != Some(tool)).then_some(tool);
+            drawing_writer.send(DrawingEvent::Tool(tool));
+        }
+    }
+}
+
+fn apply_drawing_events(
+    mut events: EventReader<DrawingEvent>,
+    mut drawing: ResMut<Drawing>,
+    tx_output_event: Res<TxOutputEvent>,
+) {
+    for event in events.read() {
+        match event {
+            DrawingEvent::Tool(tool) => {
+                if drawing.tool != *tool {
+                    drawing.tool = *tool;
+                    tx_output_event.emit(OutputEvent::DrawingToolChanged(*tool));
+                }
+            }
+            DrawingEvent::Color(color) => match parse_color(color) {
+                Some(color) => drawing.color = color,
+                None => warn!("Unsupported stroke color {}", color),
+            },
+            DrawingEvent::Width(width) => drawing.width = width.max(1.),
+        }
+    }
+}
+
+fn start_stroke(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    drawing: Res<Drawing>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let stroke = match drawing.tool {
+        Some(DrawingTool::Pen) => PenStroke {
+            points: vec![cursor_coords.current],
+            color: drawing.color,
+            width: drawing.width,
+            highlighter: false,
+        },
+        Some(DrawingTool::Highlighter) => PenStroke {
+            points: vec![cursor_coords.current],
+            color: drawing.color.with_a(HIGHLIGHTER_ALPHA),
+            width: drawing.width * HIGHLIGHTER_WIDTH_SCALE,
+            highlighter: true,
+        },
+        Some(DrawingTool::Eraser) | None => return,
+    };
+
+    commands.spawn((
+        ShapeBundle {
+            path: stroke_path(&stroke.points),
+            spatial: SpatialBundle::from_transform(Transform::from_xyz(0., 0., DRAFT_Z)),
+            ..Default::default()
+        },
+        pen_stroke_style(&stroke),
+        DraftStroke(stroke),
+        Name::new("Draft Stroke"),
+    ));
+}
+
+fn extend_stroke(
+    cursor_coords: Res<CursorCoords>,
+    mut draft_query: Query<(&mut DraftStroke, &mut Path)>,
+) {
+    let Ok((mut draft, mut path)) = draft_query.get_single_mut() else {
+        return;
+    };
+
+    let position = cursor_coords.current;
+    if draft
+        .0
+        .points
+        .last()
+        .is_some_and(|last| last.distance(position) < MIN_POINT_DISTANCE)
+    {
+        return;
+    }
+
+    draft.0.points.push(position);
+    *path = stroke_path(&draft.0.points);
+}
+
+/// Turns the drawn stroke into an item once the mouse is released.
+fn finish_stroke(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    drawing: Res<Drawing>,
+    draft_query: Query<(Entity, &DraftStroke)>,
+) {
+    let Ok((entity, draft)) = draft_query.get_single() else {
+        return;
+    };
+    let released = mouse_button_input.just_released(MouseButton::Left);
+    // Going back to selecting mid-stroke drops the stroke
+    if !released && drawing.tool.is_some() {
+        return;
+    }
+    commands.entity(entity).despawn();
+    if !released {
+        return;
+    }
+
+    // Items are positioned by their center, the points are kept relative to it
+    let Some(bounds) = points_bounds(&draft.0.points) else {
+        return;
+    };
+    let center = bounds.center();
+    spawn_pen_stroke(
+        &mut commands,
+        center.extend(0.),
+        PenStroke {
+            points: draft.0.points.iter().map(|point| *point - center).collect(),
+            ..draft.0.clone()
+        },
+    );
+}
+
+pub(crate) fn spawn_pen_stroke(
+    commands: &mut Commands,
+    position: Vec3,
+    stroke: PenStroke,
+) -> Entity {
+    // Bounds are only calculated once by bevy, and would leave out half the stroke width
+    let bounds = points_bounds(&stroke.points)
+        .unwrap_or_default()
+        .inset(stroke.width / 2.);
+
+    commands
+        .spawn((
+            ItemBundle {
+                fill: Fill::color(Color::NONE),
+                stroke: pen_stroke_style(&stroke),
+                shape: ShapeBundle {
+                    path: stroke_path(&stroke.points),
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Aabb::from_min_max(bounds.min.extend(0.), bounds.max.extend(0.)),
+            Name::new(if stroke.highlighter {
+                "Highlighter Stroke"
+            } else {
+                "Pen Stroke"
+            }),
+            stroke,
+        ))
+        .id()
+}
+
+fn pen_stroke_style(stroke: &PenStroke) -> Stroke {
+    Stroke {
+        color: stroke.color,
+        options: StrokeOptions::default()
+            .with_line_width(stroke.width)
+            .with_line_cap(LineCap::Round)
+            .with_line_join(LineJoin::Round),
+    }
+}
+
+fn points_bounds(points: &[Vec2]) -> Option<Rect> {
+    points
+        .iter()
+        .map(|point| Rect::from_corners(*point, *point))
+        .reduce(|rect, point| rect.union(point))
+}
+
+/// A smooth path through every point, fitting a Catmull-Rom spline as cubic bezier curves.
+fn stroke_path(points: &[Vec2]) -> Path {
+    let mut builder = PathBuilder::new();
+    let Some(first) = points.first() else {
+        return builder.build();
+    };
+
+    builder.move_to(*first);
+    if points.len() == 1 {
+        // A click draws a dot, round caps need a segment to draw around
+        builder.line_to(*first + Vec2::X * 0.01);
+        return builder.build();
+    }
+
+    for index in 0..points.len() - 1 {
+        let before = points[index.saturating_sub(1)];
+        let start = points[index];
+        let end = points[index + 1];
+        let after = points[(index + 2).min(points.len() - 1)];
+
+        builder.cubic_bezier_to(start + (end - before) / 6., end - (after - start) / 6., end);
+    }
+
+    builder.build()
+}
+
+/// Deletes the strokes the eraser is dragged over.
+fn erase_strokes(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    drawing: Res<Drawing>,
+    stroke_query: Query<(Entity, &PenStroke, &GlobalTransform), With<Selectable>>,
+) {
+    if drawing.tool != Some(DrawingTool::Eraser) || !mouse_button_input.pressed(MouseButton::Left) {
+        return;
+    }
+
+    for (entity, stroke, transform) in &stroke_query {
+        // Compared in the stroke's own space, so moved and rotated strokes are hit where they are
+        let position = transform
+            .affine()
+            .inverse()
+            .transform_point3(cursor_coords.current.extend(0.))
+            .xy();
+        let reach = ERASER_RADIUS + stroke.width / 2.;
+
+        let hit = match stroke.points.as_slice() {
+            [point] => point.distance(position) <= reach,
+            points => points
+                .windows(2)
+                .any(|segment| distance_to_segment(position, segment[0], segment[1]) <= reach),
+        };
+        if hit {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t =
+        ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+
+    point.distance(start + segment * t)
+}
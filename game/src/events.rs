@@ -1,21 +1,44 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
+use crate::document::BoardDocument;
+use crate::image_store::ImageStore;
+use crate::item::ItemId;
+use crate::keymap::Keymap;
 use crate::theme::ThemeMode;
 
 #[derive(Debug)]
 pub struct SharedState {
     pub name: String,
     pub window_size: (f32, f32),
+    pub image_store: ImageStore,
 }
 
 pub type Shared<T> = Arc<Mutex<T>>;
 
+/// The web app drains output events on a timer, so leave room for a few frames' worth.
+const OUTPUT_EVENT_CAPACITY: usize = 500;
+
 #[derive(Debug)]
 pub enum InputEvent {
     Theme(ThemeEvent),
     Resize(ResizeEvent),
     AddItem(AddItemEvent),
+    LoadBoard(LoadBoardEvent),
+    Undo,
+    Redo,
+    SelectItems(SelectItemsEvent),
+    DropItems(DropItemsEvent),
+    Paste(PasteEvent),
+    SelectionTool(SelectionToolEvent),
+    Select(SelectionEvent),
+    Keymap(KeymapEvent),
+    Frame(FrameEvent),
+    Connector(ConnectorEvent),
+    Drawing(DrawingEvent),
+    Shape(ShapeEvent),
+    SpanColor(SpanColorEvent),
 }
 
 #[derive(Clone, Debug, Event)]
@@ -32,14 +55,254 @@ pub struct ResizeEvent {
 #[derive(Clone, Debug, Event)]
 pub enum AddItemEvent {
     Text(String),
+    /// A data URI, or the path of an image in the [`ImageStore`]. The web app downloads
+    /// images from other URLs into the store first.
     Image(String),
+    /// A hex or `rgb()` color, anything else picks a random one.
     Swatch(String),
     PostIt(String),
+    /// A frame with the given title.
+    Frame(String),
+    Ellipse,
+    /// A rectangle with rounded corners.
+    RoundedRect,
+    Triangle,
+    /// A star with the given number of points.
+    Star(u32),
+    Line,
+    /// A regular polygon with the given number of sides.
+    Polygon(u32),
 }
 
-#[derive(Debug)]
+/// Items dropped onto the canvas at `x`/`y` in window coordinates, like the cursor.
+#[derive(Clone, Debug, Event)]
+pub struct DropItemsEvent {
+    pub x: f32,
+    pub y: f32,
+    pub items: Vec<AddItemEvent>,
+}
+
+/// Clipboard contents to add at the cursor.
+#[derive(Clone, Debug, Event)]
+pub enum PasteEvent {
+    /// A path to load the image from, like [`AddItemEvent::Image`].
+    Image(String),
+    /// Items copied from a board, colors become swatches, anything else a post-it.
+    Text(String),
+    /// The items last copied on this board, for when the system clipboard can't be read.
+    Copied,
+}
+
+/// Replaces every item on the board. `None` loads the demo board.
+#[derive(Clone, Debug, Event)]
+pub struct LoadBoardEvent {
+    pub board: Option<BoardDocument>,
+}
+
+/// Replaces the selection. Ids of items that no longer exist are ignored.
+#[derive(Clone, Debug, Event)]
+pub struct SelectItemsEvent {
+    pub items: Vec<ItemId>,
+}
+
+/// Changes the selection as a whole, from shortcuts or the web app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event)]
+pub enum SelectionEvent {
+    All,
+    /// Every item of one type, like all post-its.
+    OfType(ItemType),
+    /// Items with a color close to that of a selected item, like swatches, colored text,
+    /// shapes or pen strokes.
+    SimilarColor,
+    /// Selects the unselected items and deselects the selected ones.
+    Invert,
+}
+
+/// What dragging over an empty part of the canvas selects with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub enum SelectionTool {
+    #[default]
+    Box,
+    /// A freeform path, selecting the items it encloses.
+    Lasso,
+}
+
+#[derive(Clone, Debug, Event)]
+pub struct SelectionToolEvent {
+    pub tool: SelectionTool,
+}
+
+/// Replaces the shortcuts, e.g. with the user's overrides applied.
+#[derive(Clone, Debug, Event)]
+pub struct KeymapEvent {
+    pub keymap: Keymap,
+}
+
+/// Acts on a frame, e.g. from the web app's list of frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event)]
+pub enum FrameEvent {
+    /// Centres the view on the frame and fits it in.
+    ZoomTo(ItemId),
+    ToggleCollapsed(ItemId),
+}
+
+/// How a connector is routed between its items.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectorStyle {
+    #[default]
+    Straight,
+    /// Horizontal and vertical segments, turning halfway.
+    Elbow,
+    Curved,
+}
+
+/// The tip drawn where a connector meets the item it points to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArrowHead {
+    None,
+    #[default]
+    Open,
+    Filled,
+}
+
+/// The edge of an item a connector is attached to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectorAnchor {
+    /// The edge facing the other item, which changes as the items move.
+    #[default]
+    Auto,
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Draws and restyles connectors, from shortcuts or the web app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event)]
+pub enum ConnectorEvent {
+    /// Connects the two selected items, pointing from the one stacked below to the other.
+    Connect {
+        style: ConnectorStyle,
+        head: ArrowHead,
+    },
+    /// Changes the style of the selected connectors.
+    SetStyle(ConnectorStyle),
+    SetHead(ArrowHead),
+    SetAnchors {
+        from: ConnectorAnchor,
+        to: ConnectorAnchor,
+    },
+}
+
+/// What dragging on the canvas draws with, instead of selecting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawingTool {
+    Pen,
+    /// Wide, translucent strokes, for marking things up.
+    Highlighter,
+    /// Deletes the strokes it is dragged over.
+    Eraser,
+}
+
+/// Changes how the board is drawn on, from shortcuts or the web app.
+#[derive(Clone, Debug, Event)]
+pub enum DrawingEvent {
+    /// Draws with the tool, or goes back to selecting with `None`.
+    Tool(Option<DrawingTool>),
+    /// A hex or `rgb()` color for new strokes.
+    Color(String),
+    /// The width of new pen strokes, highlighter strokes are wider.
+    Width(f32),
+}
+
+/// The outline of a shape item, drawn to fill the item's size.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeKind {
+    Rectangle,
+    Ellipse,
+    /// Pointing up, with its base along the bottom.
+    Triangle,
+    Star {
+        points: u32,
+    },
+    /// A regular polygon, with a corner at the top.
+    Polygon {
+        sides: u32,
+    },
+    /// From the bottom left corner to the top right one.
+    Line,
+}
+
+/// Restyles the selected shapes, from the web app.
+#[derive(Clone, Debug, Event)]
+pub enum ShapeEvent {
+    /// A hex or `rgb()` color, `none` leaves the shapes unfilled.
+    Fill(String),
+    /// A hex or `rgb()` color for the outline.
+    Stroke(String),
+    StrokeWidth(f32),
+    /// Rounds the corners of every shape but ellipses and lines.
+    CornerRadius(f32),
+}
+
+/// Colors the selected text of the post-it or text being edited, or what is typed next.
+#[derive(Clone, Debug, Event)]
+pub struct SpanColorEvent {
+    /// A hex or `rgb()` color, `none` follows the theme's text color again.
+    pub color: String,
+}
+
+#[derive(Clone, Debug, Event)]
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemType {
+    PostIt,
+    Text,
+    Swatch,
+    Image,
+    Group,
+    Frame,
+    Connector,
+    Drawing,
+    Shape,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemInfo {
+    pub id: ItemId,
+    pub item_type: ItemType,
+    pub position: Vec2,
+}
+
+#[derive(Clone, Debug)]
 pub enum OutputEvent {
-    Click,
+    BoardSnapshot(BoardDocument),
+    ThemeChanged(ThemeMode),
+    /// Every selected item, empty when the selection was cleared.
+    SelectionChanged(Vec<ItemInfo>),
+    ItemsAdded(Vec<ItemInfo>),
+    ItemsRemoved(Vec<ItemId>),
+    ItemsMoved(Vec<ItemInfo>),
+    CameraChanged {
+        position: Vec2,
+        scale: f32,
+    },
+    AssetLoadFailed {
+        item: ItemId,
+        source: String,
+    },
+    /// Copied items to put on the system clipboard.
+    CopyToClipboard(String),
+    /// The shortcut to list every shortcut was pressed.
+    ShowShortcuts,
+    /// The drawing tool changed, `None` when dragging selects again.
+    DrawingToolChanged(Option<DrawingTool>),
+    /// Whether a post-it or text is being edited.
+    TextEditingChanged(bool),
 }
 
 #[derive(Clone, Resource, Deref)]
@@ -49,7 +312,17 @@ pub struct RxInputEvent(pub crossbeam_channel::Receiver<InputEvent>);
 
 #[derive(Resource, Deref, DerefMut, Clone)]
 pub struct TxOutputEvent(pub crossbeam_channel::Sender<OutputEvent>);
-#[derive(Resource, Deref, DerefMut)]
+
+impl TxOutputEvent {
+    /// Sends without blocking, the game must keep running even if the channel is full.
+    pub fn emit(&self, event: OutputEvent) {
+        if let Err(err) = self.try_send(event) {
+            warn!("Could not send output event: {}", err);
+        }
+    }
+}
+
+#[derive(Resource, Deref, DerefMut, Clone)]
 pub struct RxOutputEvent(pub crossbeam_channel::Receiver<OutputEvent>);
 
 pub struct DuplexEventsPlugin {
@@ -62,7 +335,7 @@ type ExternalChannels = (TxInputEvent, RxOutputEvent);
 impl DuplexEventsPlugin {
     pub fn create() -> (ExternalChannels, Self) {
         let (tx_input_event, rx_input_event) = crossbeam_channel::bounded(50);
-        let (tx_output_event, rx_output_event) = crossbeam_channel::bounded(50);
+        let (tx_output_event, rx_output_event) = crossbeam_channel::bounded(OUTPUT_EVENT_CAPACITY);
         (
             (TxInputEvent(tx_input_event), RxOutputEvent(rx_output_event)),
             Self {
@@ -94,9 +367,22 @@ impl Plugin for DuplexEventsPlugin {
 
         app.insert_resource(rx_input_event)
             .insert_resource(tx_output_event)
-            .init_resource::<Events<ThemeEvent>>()
-            .init_resource::<Events<ResizeEvent>>()
-            .init_resource::<Events<AddItemEvent>>()
+            .add_event::<ThemeEvent>()
+            .add_event::<ResizeEvent>()
+            .add_event::<AddItemEvent>()
+            .add_event::<LoadBoardEvent>()
+            .add_event::<HistoryEvent>()
+            .add_event::<SelectItemsEvent>()
+            .add_event::<DropItemsEvent>()
+            .add_event::<PasteEvent>()
+            .add_event::<SelectionToolEvent>()
+            .add_event::<SelectionEvent>()
+            .add_event::<KeymapEvent>()
+            .add_event::<FrameEvent>()
+            .add_event::<ConnectorEvent>()
+            .add_event::<DrawingEvent>()
+            .add_event::<ShapeEvent>()
+            .add_event::<SpanColorEvent>()
             .add_systems(PreUpdate, input_events_system);
     }
 }
@@ -106,6 +392,28 @@ fn input_events_system(
     mut theme_event_writer: EventWriter<ThemeEvent>,
     mut resize_event_writer: EventWriter<ResizeEvent>,
     mut add_item_event_writer: EventWriter<AddItemEvent>,
+    mut load_board_event_writer: EventWriter<LoadBoardEvent>,
+    mut history_event_writer: EventWriter<HistoryEvent>,
+    mut select_items_event_writer: EventWriter<SelectItemsEvent>,
+    mut drop_items_event_writer: EventWriter<DropItemsEvent>,
+    mut paste_event_writer: EventWriter<PasteEvent>,
+    mut selection_tool_event_writer: EventWriter<SelectionToolEvent>,
+    mut selection_event_writer: EventWriter<SelectionEvent>,
+    mut keymap_event_writer: EventWriter<KeymapEvent>,
+    // Systems take at most 16 parameters, so the writers for item tools are grouped
+    (
+        mut frame_event_writer,
+        mut connector_event_writer,
+        mut drawing_event_writer,
+        mut shape_event_writer,
+        mut span_color_event_writer,
+    ): (
+        EventWriter<FrameEvent>,
+        EventWriter<ConnectorEvent>,
+        EventWriter<DrawingEvent>,
+        EventWriter<ShapeEvent>,
+        EventWriter<SpanColorEvent>,
+    ),
 ) {
     for input_event in rx_input_event.try_iter() {
         match input_event {
@@ -119,6 +427,62 @@ fn input_events_system(
             InputEvent::AddItem(event) => {
                 add_item_event_writer.send(event);
             }
+
+            InputEvent::LoadBoard(event) => {
+                load_board_event_writer.send(event);
+            }
+
+            InputEvent::Undo => {
+                history_event_writer.send(HistoryEvent::Undo);
+            }
+
+            InputEvent::Redo => {
+                history_event_writer.send(HistoryEvent::Redo);
+            }
+
+            InputEvent::SelectItems(event) => {
+                select_items_event_writer.send(event);
+            }
+
+            InputEvent::DropItems(event) => {
+                drop_items_event_writer.send(event);
+            }
+
+            InputEvent::Paste(event) => {
+                paste_event_writer.send(event);
+            }
+
+            InputEvent::SelectionTool(event) => {
+                selection_tool_event_writer.send(event);
+            }
+
+            InputEvent::Select(event) => {
+                selection_event_writer.send(event);
+            }
+
+            InputEvent::Keymap(event) => {
+                keymap_event_writer.send(event);
+            }
+
+            InputEvent::Frame(event) => {
+                frame_event_writer.send(event);
+            }
+
+            InputEvent::Connector(event) => {
+                connector_event_writer.send(event);
+            }
+
+            InputEvent::Drawing(event) => {
+                drawing_event_writer.send(event);
+            }
+
+            InputEvent::Shape(event) => {
+                shape_event_writer.send(event);
+            }
+
+            InputEvent::SpanColor(event) => {
+                span_color_event_writer.send(event);
+            }
         }
     }
 }
@@ -0,0 +1,494 @@
+use bevy::{
+    render::primitives::Aabb,
+    sprite::Anchor,
+    text::{BreakLineOn, Text2dBounds},
+    utils::HashSet,
+};
+use bevy_pancam::PanCam;
+
+use crate::{
+    connector::Connector,
+    document::FontDocument,
+    events::{AddItemEvent, FrameEvent},
+    history::History,
+    item::{Item, ItemBundle, ItemDeletion, ItemId, ItemSize, SpawnItemEvent},
+    prelude::*,
+    rich_text::RichText,
+    select::{
+        components::{Selectable, Selected},
+        utils::contains_point,
+        SelectionMoved,
+    },
+    text_edit::TextEditor,
+    theme::{Theme, ThemeDidChange},
+    FontFamilyKind, FontStack, FontVariant,
+};
+
+const FRAME_SIZE: Vec2 = Vec2::new(1600., 1000.);
+const FRAME_STROKE_WIDTH: f32 = 2.0;
+const HEADER_HEIGHT: f32 = 80.0;
+const TITLE_PADDING: f32 = 24.0;
+/// Frames stack below every other item, which start at a z of 1.
+pub(crate) const FRAME_Z: f32 = 0.1;
+/// Small enough for boards to be reloaded many times before frames reach the other items.
+pub(crate) const FRAME_Z_STEP: f32 = 0.00001;
+/// How much room to leave around a frame when zooming to it.
+const ZOOM_MARGIN: f32 = 1.1;
+
+pub struct FramePlugin;
+
+impl Plugin for FramePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                add_frame,
+                resize_frames,
+                frame_theme_change,
+                capture_frame_contents,
+                move_frame_contents,
+                hide_collapsed_contents,
+                reveal_released_contents,
+                prune_frame_contents.in_set(ItemDeletion::CleanUp),
+                apply_frame_events,
+            ),
+        );
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub(crate) struct Frame {
+    pub collapsed: bool,
+}
+
+/// The items a frame holds, by id so they are kept when the board is saved.
+#[derive(Component, Debug, Default)]
+pub(crate) struct FrameContents(pub Vec<ItemId>);
+
+/// Where the frame was when its contents last followed it.
+#[derive(Component)]
+struct FramePosition(Vec2);
+
+#[derive(Component)]
+struct FrameHeader;
+
+#[derive(Component)]
+pub(crate) struct FrameTitle;
+
+/// The font of frame titles.
+pub(crate) fn frame_title_font(font_stack: &FontStack) -> FontDocument {
+    FontDocument {
+        family: FontFamilyKind::Title,
+        variant: FontVariant::Bold,
+        size: font_stack.size.medium,
+    }
+}
+
+fn add_frame(
+    mut commands: Commands,
+    mut events: EventReader<SpawnItemEvent>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    camera_query: Query<&Transform, With<PanCam>>,
+) {
+    for event in events.read() {
+        let AddItemEvent::Frame(title) = &event.item else {
+            continue;
+        };
+
+        // Place new frames in the middle of the viewport, like images
+        let position = event.position.unwrap_or_else(|| {
+            camera_query
+                .get_single()
+                .map(|transform| transform.translation.xy())
+                .unwrap_or_default()
+        });
+
+        spawn_frame(
+            &mut commands,
+            &theme,
+            &font_stack,
+            position.extend(0.),
+            RichText::plain(title.as_str(), frame_title_font(&font_stack)),
+            Frame::default(),
+            FrameContents::default(),
+        );
+    }
+}
+
+pub(crate) fn spawn_frame(
+    commands: &mut Commands,
+    theme: &Theme,
+    font_stack: &FontStack,
+    position: Vec3,
+    title: RichText,
+    frame: Frame,
+    contents: FrameContents,
+) -> Entity {
+    commands
+        .spawn((
+            ItemBundle {
+                fill: Fill::color(theme.frame_bg_color),
+                stroke: Stroke::new(theme.frame_stroke_color, FRAME_STROKE_WIDTH),
+                shape: ShapeBundle {
+                    path: GeometryBuilder::build_as(&shapes::Rectangle {
+                        extents: FRAME_SIZE,
+                        ..Default::default()
+                    }),
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            frame,
+            contents,
+            FramePosition(position.xy()),
+            ItemSize(FRAME_SIZE),
+            Name::new("Frame"),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shapes::Rectangle {
+                        extents: header_size(FRAME_SIZE),
+                        ..Default::default()
+                    }),
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(
+                        header_offset(FRAME_SIZE).extend(FRAME_Z_STEP * 0.2),
+                    )),
+                    ..Default::default()
+                },
+                Fill::color(theme.frame_header_color),
+                FrameHeader,
+                Name::new("Frame Header"),
+            ));
+
+            builder.spawn((
+                Text2dBundle {
+                    text: Text {
+                        sections: title.sections(font_stack, theme.default_text_color),
+                        alignment: TextAlignment::Left,
+                        linebreak_behavior: BreakLineOn::NoWrap,
+                    },
+                    text_anchor: Anchor::CenterLeft,
+                    text_2d_bounds: Text2dBounds {
+                        size: title_bounds(FRAME_SIZE),
+                    },
+                    transform: Transform::from_translation(
+                        title_offset(FRAME_SIZE).extend(FRAME_Z_STEP * 0.4),
+                    ),
+                    ..default()
+                },
+                title,
+                FrameTitle,
+                Name::new("Frame Title"),
+            ));
+        })
+        .id()
+}
+
+fn header_size(size: Vec2) -> Vec2 {
+    Vec2::new(size.x, HEADER_HEIGHT)
+}
+
+/// The centre of the header, along the top edge of the frame.
+fn header_offset(size: Vec2) -> Vec2 {
+    Vec2::new(0., (size.y - HEADER_HEIGHT) / 2.)
+}
+
+fn title_offset(size: Vec2) -> Vec2 {
+    Vec2::new(-size.x / 2. + TITLE_PADDING, header_offset(size).y)
+}
+
+fn title_bounds(size: Vec2) -> Vec2 {
+    Vec2::new(size.x - TITLE_PADDING * 2., HEADER_HEIGHT)
+}
+
+type FrameShapeQuery = (
+    Entity,
+    &'static ItemSize,
+    &'static Frame,
+    &'static Children,
+    &'static mut Path,
+);
+
+type FrameReshapedFilter = Or<(Changed<ItemSize>, Changed<Frame>)>;
+
+type FrameHeaderFilter = (With<FrameHeader>, Without<ItemSize>);
+
+type FrameTitleFilter = (With<FrameTitle>, Without<FrameHeader>);
+
+/// Redraws frames when they are resized or collapsed, collapsed frames only show their header.
+fn resize_frames(
+    mut commands: Commands,
+    mut frame_query: Query<FrameShapeQuery, FrameReshapedFilter>,
+    mut header_query: Query<(&mut Path, &mut Transform), FrameHeaderFilter>,
+    mut title_query: Query<(&mut Transform, &mut Text2dBounds), FrameTitleFilter>,
+) {
+    for (entity, size, frame, children, mut path) in &mut frame_query {
+        let size = size.0;
+        let (extents, center) = if frame.collapsed {
+            (header_size(size), header_offset(size))
+        } else {
+            (size, Vec2::ZERO)
+        };
+
+        *path = GeometryBuilder::build_as(&shapes::Rectangle {
+            extents,
+            origin: shapes::RectangleOrigin::CustomCenter(center),
+        });
+        commands.entity(entity).insert(Aabb::from_min_max(
+            (center - extents / 2.).extend(0.),
+            (center + extents / 2.).extend(0.),
+        ));
+
+        for child in children {
+            if let Ok((mut header_path, mut transform)) = header_query.get_mut(*child) {
+                *header_path = GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: header_size(size),
+                    ..Default::default()
+                });
+                transform.translation = header_offset(size).extend(transform.translation.z);
+            }
+            if let Ok((mut transform, mut bounds)) = title_query.get_mut(*child) {
+                transform.translation = title_offset(size).extend(transform.translation.z);
+                bounds.size = title_bounds(size);
+            }
+        }
+    }
+}
+
+type FrameBodyFilter = (With<Frame>, Without<FrameHeader>);
+
+/// Editors render their own sections.
+type UneditedTitleFilter = (With<FrameTitle>, Without<TextEditor>);
+
+fn frame_theme_change(
+    mut theme_event_reader: EventReader<ThemeDidChange>,
+    font_stack: Res<FontStack>,
+    mut frame_query: Query<(&mut Fill, &mut Stroke), FrameBodyFilter>,
+    mut header_query: Query<&mut Fill, With<FrameHeader>>,
+    mut title_query: Query<(&RichText, &mut Text), UneditedTitleFilter>,
+) {
+    for event in theme_event_reader.read() {
+        let theme = &event.theme;
+
+        for (mut fill, mut stroke) in &mut frame_query {
+            fill.color = theme.frame_bg_color;
+            stroke.color = theme.frame_stroke_color;
+        }
+        for mut fill in &mut header_query {
+            fill.color = theme.frame_header_color;
+        }
+        for (rich_text, mut text) in &mut title_query {
+            text.sections = rich_text.sections(&font_stack, theme.default_text_color);
+        }
+    }
+}
+
+/// Items that can be put in a frame, frames and connectors can't.
+type FrameableFilter = (
+    With<Item>,
+    Without<Frame>,
+    Without<Connector>,
+    Without<Parent>,
+);
+
+type FrameCaptureQuery = (
+    &'static GlobalTransform,
+    &'static Aabb,
+    &'static Frame,
+    &'static mut FrameContents,
+);
+
+/// Items dropped inside a frame join it, and leave any frame they were dragged out of.
+fn capture_frame_contents(
+    mut selection_moved_reader: EventReader<SelectionMoved>,
+    new_item_query: Query<Entity, Added<Item>>,
+    new_frame_query: Query<(), Added<Frame>>,
+    // New items aren't propagated yet, so their own transform is their place on the canvas
+    item_query: Query<(&ItemId, &Transform), FrameableFilter>,
+    mut frame_query: Query<FrameCaptureQuery>,
+) {
+    let mut dropped = selection_moved_reader
+        .read()
+        .flat_map(|event| event.entities.iter().copied())
+        .collect::<Vec<_>>();
+    // Frames spawned from a board already know their contents
+    if new_frame_query.is_empty() {
+        dropped.extend(&new_item_query);
+    }
+
+    for (id, transform) in item_query.iter_many(&dropped) {
+        let position = transform.translation.xy();
+
+        // Frames are stacked below every other item, the topmost one under the item wins
+        let target = frame_query
+            .iter()
+            .enumerate()
+            .filter(|(_, (frame_transform, aabb, frame, _))| {
+                !frame.collapsed && contains_point(frame_transform, aabb, position)
+            })
+            .max_by(|(_, (a, ..)), (_, (b, ..))| a.translation().z.total_cmp(&b.translation().z))
+            .map(|(index, _)| index);
+
+        for (index, (_, _, _, mut contents)) in frame_query.iter_mut().enumerate() {
+            let contains = contents.0.contains(id);
+            if Some(index) == target && !contains {
+                contents.0.push(*id);
+            } else if Some(index) != target && contains {
+                contents.0.retain(|content| content != id);
+            }
+        }
+    }
+}
+
+type MovedFrameQuery = (
+    &'static Transform,
+    Ref<'static, ItemSize>,
+    &'static FrameContents,
+    &'static mut FramePosition,
+);
+
+/// Selected items are dragged along with the frame already.
+type UnselectedContentFilter = (
+    With<Item>,
+    Without<Frame>,
+    Without<Selected>,
+    Without<Parent>,
+);
+
+/// Moves the contents of frames by as much as their frame moved.
+fn move_frame_contents(
+    mut frame_query: Query<MovedFrameQuery, (With<Frame>, Changed<Transform>)>,
+    mut item_query: Query<(&ItemId, &mut Transform), UnselectedContentFilter>,
+) {
+    for (transform, size, contents, mut position) in &mut frame_query {
+        let offset = transform.translation.xy() - position.0;
+        position.0 = transform.translation.xy();
+
+        // Resizing moves the frame's centre, but its contents stay in place
+        if offset == Vec2::ZERO || size.is_changed() {
+            continue;
+        }
+
+        for (id, mut transform) in &mut item_query {
+            if contents.0.contains(id) {
+                transform.translation += offset.extend(0.);
+            }
+        }
+    }
+}
+
+type FrameContentFilter = (With<Item>, Without<Frame>, Without<Parent>);
+
+type FrameContentsChangedFilter = Or<(Changed<Frame>, Changed<FrameContents>)>;
+
+/// Hides the contents of collapsed frames, and shows them again when the frame is expanded.
+fn hide_collapsed_contents(
+    mut commands: Commands,
+    frame_query: Query<(&Frame, &FrameContents), FrameContentsChangedFilter>,
+    mut item_query: Query<(Entity, &ItemId, &mut Visibility), FrameContentFilter>,
+) {
+    for (frame, contents) in &frame_query {
+        for (entity, id, mut visibility) in &mut item_query {
+            if !contents.0.contains(id) {
+                continue;
+            }
+
+            if frame.collapsed && *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+                commands.entity(entity).remove::<(Selectable, Selected)>();
+            } else if !frame.collapsed && *visibility == Visibility::Hidden {
+                *visibility = Visibility::Inherited;
+                commands.entity(entity).insert(Selectable);
+            }
+        }
+    }
+}
+
+/// Shows the items of collapsed frames that were deleted.
+fn reveal_released_contents(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Frame>,
+    frame_query: Query<(&Frame, &FrameContents)>,
+    mut item_query: Query<(Entity, &ItemId, &mut Visibility), FrameContentFilter>,
+) {
+    if removed.read().count() == 0 {
+        return;
+    }
+
+    for (entity, id, mut visibility) in &mut item_query {
+        let held = frame_query
+            .iter()
+            .any(|(frame, contents)| frame.collapsed && contents.0.contains(id));
+        if *visibility == Visibility::Hidden && !held {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).insert(Selectable);
+        }
+    }
+}
+
+/// Forgets deleted items, so a frame only lists what it holds.
+fn prune_frame_contents(
+    mut removed: RemovedComponents<Item>,
+    history: Res<History>,
+    item_query: Query<&ItemId, With<Item>>,
+    mut frame_query: Query<&mut FrameContents>,
+) {
+    // Undo restores frames with their contents as they were, and loading a board replaces
+    // items with new ones that keep their ids
+    if removed.read().count() == 0 || history.is_restoring() {
+        return;
+    }
+
+    let ids = item_query.iter().collect::<HashSet<_>>();
+    for mut contents in &mut frame_query {
+        if contents.0.iter().any(|id| !ids.contains(id)) {
+            contents.0.retain(|id| ids.contains(id));
+        }
+    }
+}
+
+fn apply_frame_events(
+    mut events: EventReader<FrameEvent>,
+    mut frame_query: Query<(&ItemId, &GlobalTransform, &ItemSize, &mut Frame)>,
+    mut camera_query: Query<(&PanCam, &mut Transform, &mut OrthographicProjection), Without<Frame>>,
+) {
+    for event in events.read() {
+        match event {
+            FrameEvent::ToggleCollapsed(id) => {
+                if let Some((_, _, _, mut frame)) = frame_query
+                    .iter_mut()
+                    .find(|(frame_id, ..)| *frame_id == id)
+                {
+                    frame.collapsed = !frame.collapsed;
+                }
+            }
+            FrameEvent::ZoomTo(id) => {
+                let Some((_, frame_transform, size, _)) =
+                    frame_query.iter().find(|(frame_id, ..)| *frame_id == id)
+                else {
+                    continue;
+                };
+                let Ok((pan_cam, mut transform, mut projection)) = camera_query.get_single_mut()
+                else {
+                    continue;
+                };
+
+                transform.translation = frame_transform
+                    .translation()
+                    .xy()
+                    .extend(transform.translation.z);
+
+                // The visible area grows with the scale
+                let visible = projection.area.size() / projection.scale;
+                let scale = (size.0 / visible).max_element() * ZOOM_MARGIN;
+                projection.scale = scale.clamp(
+                    pan_cam.min_scale,
+                    pan_cam.max_scale.unwrap_or(f32::INFINITY),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use bevy::render::primitives::Aabb;
+
+use crate::{
+    canvas::CursorCoords,
+    drawing::not_drawing,
+    item::{item_aabb, Item, ItemBundle},
+    keymap::{Action, Keymap},
+    prelude::*,
+    select::{
+        components::{Selectable, Selected},
+        select_entities,
+        utils::{contains_point, get_corners},
+    },
+    text_edit::{is_double_click, not_editing_text},
+};
+
+const ENTERED_GROUP_COLOR: Color = Palette::PURPLE_600;
+const ENTERED_GROUP_STROKE_WIDTH: f32 = 2.0;
+
+pub struct GroupPlugin;
+
+impl Plugin for GroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnteredGroups>()
+            .add_event::<GroupEvent>()
+            .add_systems(
+                Update,
+                (
+                    group_shortcuts.run_if(not_editing_text),
+                    apply_group_events.after(group_shortcuts),
+                    update_group_bounds,
+                    leave_group_on_click_away.before(select_entities),
+                    enter_group.run_if(not_drawing).after(select_entities),
+                    leave_group_shortcut.run_if(not_editing_text),
+                    outline_entered_groups,
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Group;
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+enum GroupEvent {
+    /// Groups the selected items.
+    Group,
+    /// Dissolves the selected groups, selecting their members instead.
+    Ungroup,
+}
+
+/// The groups whose members can be selected on their own, innermost last.
+#[derive(Resource, Default)]
+struct EnteredGroups(Vec<Entity>);
+
+/// Spawns an empty group, its bounds follow the members pushed into it.
+pub(crate) fn spawn_group(commands: &mut Commands, position: Vec3) -> Entity {
+    let transform = Transform::from_translation(position);
+
+    commands
+        .spawn((
+            ItemBundle {
+                fill: Fill::color(Color::NONE),
+                shape: ShapeBundle {
+                    spatial: SpatialBundle {
+                        transform,
+                        // Selecting the group fits the selected rect before the transform is
+                        // propagated
+                        global_transform: GlobalTransform::from(transform),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            item_aabb(Vec2::ZERO),
+            Group,
+            Name::new("Group"),
+        ))
+        .id()
+}
+
+/// How many stacking steps an item takes up, counting every item in it when it is a group.
+pub(crate) fn stacked_size(entity: Entity, group_query: &Query<&Children, With<Group>>) -> usize {
+    group_query.get(entity).map_or(1, |children| {
+        1 + children
+            .iter()
+            .map(|child| stacked_size(*child, group_query))
+            .sum::<usize>()
+    })
+}
+
+fn group_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut group_writer: EventWriter<GroupEvent>,
+) {
+    if keymap.just_pressed(Action::Group, &keys) {
+        group_writer.send(GroupEvent::Group);
+    }
+    if keymap.just_pressed(Action::Ungroup, &keys) {
+        group_writer.send(GroupEvent::Ungroup);
+    }
+}
+
+fn apply_group_events(
+    mut commands: Commands,
+    mut events: EventReader<GroupEvent>,
+    selected_query: Query<(Entity, &Transform, &Aabb, Has<Parent>), With<Selected>>,
+    selected_group_query: Query<(Entity, &Transform, &Children, Option<&Parent>), With<Selected>>,
+    group_query: Query<&Children, With<Group>>,
+    member_query: Query<&Transform, With<Item>>,
+) {
+    for event in events.read() {
+        match event {
+            GroupEvent::Group => {
+                // Only items on the canvas itself are grouped, not the members of an entered group
+                if selected_query.iter().count() < 2
+                    || selected_query
+                        .iter()
+                        .any(|(_, _, _, has_parent)| has_parent)
+                {
+                    continue;
+                }
+
+                let mut members = selected_query
+                    .iter()
+                    .map(|(entity, transform, aabb, _)| (entity, transform, aabb))
+                    .collect::<Vec<_>>();
+                members.sort_by(|(_, a, _), (_, b, _)| a.translation.z.total_cmp(&b.translation.z));
+
+                let Some(rect) = local_bounds(
+                    members
+                        .iter()
+                        .map(|(_, transform, aabb)| (*transform, *aabb)),
+                ) else {
+                    continue;
+                };
+                let center = rect.center();
+
+                // `ItemCounter` stacks the group on top, members keep their order inside it
+                let group = spawn_group(&mut commands, center.extend(0.));
+                let mut z = 1.;
+                for (entity, transform, _) in &members {
+                    let translation = (transform.translation.xy() - center).extend(z);
+                    z += stacked_size(*entity, &group_query) as f32;
+
+                    commands
+                        .entity(*entity)
+                        .remove::<(Selectable, Selected)>()
+                        .insert(transform.with_translation(translation));
+                }
+                commands
+                    .entity(group)
+                    .push_children(
+                        &members
+                            .iter()
+                            .map(|(entity, _, _)| *entity)
+                            .collect::<Vec<_>>(),
+                    )
+                    .insert(Selected::new(center));
+            }
+            GroupEvent::Ungroup => {
+                for (group, group_transform, children, parent) in &selected_group_query {
+                    if !group_query.contains(group) {
+                        continue;
+                    }
+
+                    // Members move up into whatever holds the group, keeping their place
+                    for member in children {
+                        let Ok(transform) = member_query.get(*member) else {
+                            continue;
+                        };
+                        let transform = group_transform.mul_transform(*transform);
+
+                        let mut member = commands.entity(*member);
+                        match parent {
+                            Some(parent) => member.set_parent(parent.get()),
+                            None => member.remove_parent(),
+                        };
+                        member.insert((
+                            transform,
+                            Selectable,
+                            Selected::new(transform.translation.xy()),
+                        ));
+                    }
+                    commands.entity(group).despawn();
+                }
+            }
+        }
+    }
+}
+
+/// Fits the bounds of each group around its members, and removes groups left empty.
+fn update_group_bounds(
+    mut commands: Commands,
+    mut group_query: Query<(Entity, Option<&Children>, &Aabb, &mut Path), With<Group>>,
+    member_query: Query<(&Transform, &Aabb), With<Item>>,
+) {
+    for (entity, children, aabb, mut path) in &mut group_query {
+        let members = children.map(|children| member_query.iter_many(children));
+        let Some(rect) = members.and_then(local_bounds) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let bounds = Aabb::from_min_max(rect.min.extend(0.), rect.max.extend(0.));
+        if bounds.center == aabb.center && bounds.half_extents == aabb.half_extents {
+            continue;
+        }
+
+        commands.entity(entity).insert(bounds);
+        *path = GeometryBuilder::build_as(&shapes::Rectangle {
+            extents: rect.size(),
+            origin: shapes::RectangleOrigin::CustomCenter(rect.center()),
+        });
+    }
+}
+
+/// The rect surrounding items, in the space of their parent.
+fn local_bounds<'a>(items: impl IntoIterator<Item = (&'a Transform, &'a Aabb)>) -> Option<Rect> {
+    items
+        .into_iter()
+        .flat_map(|(transform, aabb)| get_corners(&GlobalTransform::from(*transform), aabb))
+        .map(|corner| Rect::from_corners(corner, corner))
+        .reduce(|rect, corner| rect.union(corner))
+}
+
+/// The topmost of `items` under `position`.
+fn topmost_at<'a>(
+    items: impl IntoIterator<Item = (Entity, &'a GlobalTransform, &'a Aabb)>,
+    position: Vec2,
+) -> Option<Entity> {
+    items
+        .into_iter()
+        .filter(|(_, transform, aabb)| contains_point(transform, aabb, position))
+        .max_by(|(_, a, _), (_, b, _)| a.translation().z.total_cmp(&b.translation().z))
+        .map(|(entity, _, _)| entity)
+}
+
+/// Double clicking a group enters it and selects the member under the cursor.
+fn enter_group(
+    mut commands: Commands,
+    time: Res<Time>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    mut entered_groups: ResMut<EnteredGroups>,
+    selectable_query: Query<(Entity, &GlobalTransform, &Aabb), With<Selectable>>,
+    group_query: Query<&Children, With<Group>>,
+    member_query: Query<(Entity, &GlobalTransform, &Aabb, &Transform), With<Item>>,
+    mut last_click: Local<Option<(f64, Vec2)>>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let position = cursor_coords.current;
+    if !is_double_click(&mut last_click, time.elapsed_seconds_f64(), position) {
+        return;
+    }
+
+    let Some(group) = topmost_at(&selectable_query, position) else {
+        return;
+    };
+    let Ok(children) = group_query.get(group) else {
+        return;
+    };
+
+    commands.entity(group).remove::<(Selectable, Selected)>();
+    for (member, _, _, _) in member_query.iter_many(children) {
+        commands.entity(member).insert(Selectable);
+    }
+    let clicked = topmost_at(
+        member_query
+            .iter_many(children)
+            .map(|(entity, global_transform, aabb, _)| (entity, global_transform, aabb)),
+        position,
+    );
+    if let Some((member, _, _, transform)) =
+        clicked.and_then(|member| member_query.get(member).ok())
+    {
+        commands
+            .entity(member)
+            .insert(Selected::new(transform.translation.xy()));
+    }
+
+    entered_groups.0.push(group);
+}
+
+/// Leaves the entered groups a click lands outside of.
+fn leave_group_on_click_away(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    mut entered_groups: ResMut<EnteredGroups>,
+    group_query: Query<(&GlobalTransform, &Aabb, &Children), With<Group>>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    while let Some(group) = entered_groups.0.last().copied() {
+        match group_query.get(group) {
+            Ok((transform, aabb, _)) if contains_point(transform, aabb, cursor_coords.current) => {
+                break;
+            }
+            Ok((_, _, children)) => leave_group(&mut commands, group, children),
+            // Ungrouped, deleted or replaced by an undo
+            Err(_) => {}
+        }
+        entered_groups.0.pop();
+    }
+}
+
+fn leave_group_shortcut(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut entered_groups: ResMut<EnteredGroups>,
+    group_query: Query<(&Transform, &Children), With<Group>>,
+) {
+    if !keymap.just_pressed(Action::ExitGroup, &keys) {
+        return;
+    }
+
+    while let Some(group) = entered_groups.0.pop() {
+        if let Ok((transform, children)) = group_query.get(group) {
+            leave_group(&mut commands, group, children);
+            // Leaving selects the group, like clicking it
+            commands
+                .entity(group)
+                .insert(Selected::new(transform.translation.xy()));
+            break;
+        }
+    }
+}
+
+/// Makes the group selectable as one again.
+fn leave_group(commands: &mut Commands, group: Entity, children: &Children) {
+    commands.entity(group).insert(Selectable);
+    for member in children {
+        commands.entity(*member).remove::<(Selectable, Selected)>();
+    }
+}
+
+/// Outlines the entered groups, so it is clear which items can be edited on their own.
+fn outline_entered_groups(
+    entered_groups: Res<EnteredGroups>,
+    mut group_query: Query<(Entity, &mut Stroke), With<Group>>,
+) {
+    if !entered_groups.is_changed() {
+        return;
+    }
+
+    for (entity, mut stroke) in &mut group_query {
+        *stroke = if entered_groups.0.contains(&entity) {
+            Stroke::new(ENTERED_GROUP_COLOR, ENTERED_GROUP_STROKE_WIDTH)
+        } else {
+            Stroke::new(Color::NONE, 0.)
+        };
+    }
+}
@@ -0,0 +1,676 @@
+use std::collections::VecDeque;
+
+use bevy::utils::{HashMap, HashSet};
+
+use crate::{
+    connector::Connector,
+    document::{BoardDocument, ItemDocument},
+    events::{HistoryEvent, LoadBoardEvent, ThemeEvent},
+    frame::Frame,
+    item::{Item, ItemDeletion, ItemId, RestackItems},
+    keymap::{Action, Keymap},
+    prelude::*,
+    rich_text::RichText,
+    select::{SelectionMoved, SelectionTransformed},
+    shape::ShapeItem,
+    text_edit::{not_editing_text, TextEditor},
+    theme::{Theme, ThemeDidChange, ThemeMode},
+    FontStack, ImageCache,
+};
+
+const MAX_HISTORY: usize = 100;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<History>()
+            .add_systems(
+                Update,
+                (
+                    reset_history,
+                    history_shortcuts.run_if(not_editing_text),
+                    apply_history_events,
+                )
+                    .chain()
+                    // Applied along with the deletions, so the clean up can tell it apart
+                    .before(ItemDeletion::Delete),
+            )
+            // Runs after the frame's commands are applied, so spawned and despawned items are
+            // visible to the capture
+            .add_systems(PostUpdate, (detect_edits, record_history).chain());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditAction {
+    Add,
+    Delete,
+    Move,
+    Theme,
+    Text,
+    Collapse,
+    Restyle,
+}
+
+/// The board as of the last recorded edit, to find the items the next edit changed.
+#[derive(Clone, Debug, PartialEq)]
+struct BoardState {
+    /// Top-level items, with the items in groups inside their group. Their z is left at 0,
+    /// `order` keeps their stacking.
+    items: HashMap<ItemId, ItemDocument>,
+    /// Item ids, from the bottom of the stack to the top.
+    order: Vec<ItemId>,
+    theme: ThemeMode,
+}
+
+impl BoardState {
+    fn capture(world: &World) -> Self {
+        let board = BoardDocument::capture(world);
+
+        Self {
+            order: board.items.iter().map(|item| item.id).collect(),
+            items: board
+                .items
+                .into_iter()
+                .map(|item| (item.id, ItemDocument { z: 0., ..item }))
+                .collect(),
+            theme: world.resource::<Theme>().mode,
+        }
+    }
+
+    /// The edit that turns `self` into `next`, if anything changed.
+    fn diff(&self, action: EditAction, next: &Self) -> Option<HistoryEntry> {
+        let indexed = |state: &Self, id: &ItemId| {
+            let index = state.order.iter().position(|other| other == id)?;
+            Some((index, state.items.get(id)?.clone()))
+        };
+
+        let mut changes = Vec::new();
+        for id in &self.order {
+            if self.items.get(id) != next.items.get(id) {
+                changes.push(ItemChange {
+                    before: indexed(self, id),
+                    after: indexed(next, id),
+                });
+            }
+        }
+        for id in &next.order {
+            if !self.items.contains_key(id) {
+                changes.push(ItemChange {
+                    before: None,
+                    after: indexed(next, id),
+                });
+            }
+        }
+
+        // Items that were only added or removed keep the others in their order
+        let kept = |state: &Self, other: &Self| {
+            state
+                .order
+                .iter()
+                .filter(|id| other.items.contains_key(id))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let order = (kept(self, next) != kept(next, self))
+            .then(|| (self.order.clone(), next.order.clone()));
+
+        let theme = (self.theme != next.theme).then_some((self.theme, next.theme));
+
+        if changes.is_empty() && order.is_none() && theme.is_none() {
+            return None;
+        }
+
+        Some(HistoryEntry {
+            action,
+            changes,
+            order,
+            theme,
+        })
+    }
+}
+
+/// An item before and after an edit, along with its index in the stacking order.
+/// Added items have nothing before, deleted ones nothing after.
+struct ItemChange {
+    before: Option<(usize, ItemDocument)>,
+    after: Option<(usize, ItemDocument)>,
+}
+
+impl ItemChange {
+    fn id(&self) -> Option<ItemId> {
+        self.before
+            .as_ref()
+            .or(self.after.as_ref())
+            .map(|(_, item)| item.id)
+    }
+}
+
+/// The items an edit changed, to undo or redo it.
+struct HistoryEntry {
+    action: EditAction,
+    changes: Vec<ItemChange>,
+    /// The order before and after the edit, only when it restacked items it kept.
+    order: Option<(Vec<ItemId>, Vec<ItemId>)>,
+    theme: Option<(ThemeMode, ThemeMode)>,
+}
+
+impl HistoryEntry {
+    /// Applies the edit to `state`, or reverts it when undoing.
+    fn apply(&self, state: &mut BoardState, undo: bool) {
+        let side = |change: &ItemChange| {
+            if undo {
+                (&change.after, &change.before)
+            } else {
+                (&change.before, &change.after)
+            }
+        };
+
+        for change in &self.changes {
+            let Some(id) = change.id() else {
+                continue;
+            };
+            state.items.remove(&id);
+            if let (_, Some((_, item))) = side(change) {
+                state.items.insert(id, item.clone());
+            }
+        }
+
+        match &self.order {
+            Some((before, after)) => {
+                state.order = if undo { before } else { after }.clone();
+            }
+            None => {
+                state.order.retain(|id| state.items.contains_key(id));
+                // Inserted from the bottom up, so each index is where the item ends up
+                let mut inserted = self
+                    .changes
+                    .iter()
+                    .filter_map(|change| match side(change) {
+                        (None, Some((index, item))) => Some((*index, item.id)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                inserted.sort_by_key(|(index, _)| *index);
+                for (index, id) in inserted {
+                    state.order.insert(index.min(state.order.len()), id);
+                }
+            }
+        }
+
+        if let Some((before, after)) = self.theme {
+            state.theme = if undo { before } else { after };
+        }
+    }
+}
+
+/// Edits to step back and forth through.
+/// Edits are recorded after they happen by comparing against the `current` state, only the
+/// items they changed are kept.
+#[derive(Resource, Default)]
+pub(crate) struct History {
+    current: Option<BoardState>,
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    pending: Option<EditAction>,
+    /// The next capture becomes the baseline without recording an entry.
+    rebase: bool,
+    /// An undo or redo was applied this frame, its own changes are not edits.
+    restoring: bool,
+}
+
+impl History {
+    /// An undo or redo is being applied, the items it replaced the board with are as recorded.
+    pub(crate) fn is_restoring(&self) -> bool {
+        self.restoring
+    }
+
+    fn mark(&mut self, action: EditAction) {
+        self.pending.get_or_insert(action);
+    }
+
+    fn record(&mut self, action: EditAction, state: BoardState) {
+        let entry = self
+            .current
+            .as_ref()
+            .and_then(|previous| previous.diff(action, &state));
+        self.current = Some(state);
+        // Nothing changed, e.g. a click that didn't move anything
+        let Some(entry) = entry else {
+            return;
+        };
+
+        self.undo_stack.push_back(entry);
+        if self.undo_stack.len() > MAX_HISTORY {
+            self.undo_stack.pop_front();
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Reverts the last edit, returns whether there was one.
+    fn undo(&mut self) -> bool {
+        let (Some(entry), Some(current)) = (self.undo_stack.pop_back(), self.current.as_mut())
+        else {
+            return false;
+        };
+        debug!("Undo {:?}", entry.action);
+        entry.apply(current, true);
+        self.redo_stack.push(entry);
+
+        true
+    }
+
+    /// Applies the last undone edit again, returns whether there was one.
+    fn redo(&mut self) -> bool {
+        let (Some(entry), Some(current)) = (self.redo_stack.pop(), self.current.as_mut()) else {
+            return false;
+        };
+        debug!("Redo {:?}", entry.action);
+        entry.apply(current, false);
+        self.undo_stack.push_back(entry);
+
+        true
+    }
+}
+
+fn reset_history(mut events: EventReader<LoadBoardEvent>, mut history: ResMut<History>) {
+    if events.read().last().is_some() {
+        *history = History {
+            rebase: true,
+            ..Default::default()
+        };
+    }
+}
+
+fn history_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut history_writer: EventWriter<HistoryEvent>,
+) {
+    if keymap.just_pressed(Action::Undo, &keys) {
+        history_writer.send(HistoryEvent::Undo);
+    }
+    if keymap.just_pressed(Action::Redo, &keys) {
+        history_writer.send(HistoryEvent::Redo);
+    }
+}
+
+fn apply_history_events(
+    mut commands: Commands,
+    mut events: EventReader<HistoryEvent>,
+    mut history: ResMut<History>,
+    // Group members are respawned with their group
+    item_query: Query<(Entity, &ItemId), (With<Item>, Without<Parent>)>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    asset_server: Res<AssetServer>,
+    mut image_cache: ResMut<ImageCache>,
+    mut theme_writer: EventWriter<ThemeEvent>,
+    mut restack_writer: EventWriter<RestackItems>,
+) {
+    let mut restored = false;
+    let mut changed = HashSet::new();
+    for event in events.read() {
+        let entry = match event {
+            HistoryEvent::Undo => history.undo().then(|| history.redo_stack.last()),
+            HistoryEvent::Redo => history.redo().then(|| history.undo_stack.back()),
+        };
+        if let Some(entry) = entry.flatten() {
+            restored = true;
+            changed.extend(entry.changes.iter().filter_map(ItemChange::id));
+        }
+    }
+
+    let Some(state) = history.current.as_ref().filter(|_| restored) else {
+        return;
+    };
+
+    // Only the changed items are replaced, the others stay as they are, selected or not
+    for (entity, id) in &item_query {
+        if changed.contains(id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let respawned = BoardDocument {
+        items: state
+            .order
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| changed.contains(*id))
+            .filter_map(|(z, id)| {
+                Some(ItemDocument {
+                    z: z as f32,
+                    ..state.items.get(id)?.clone()
+                })
+            })
+            .collect(),
+    };
+    respawned.spawn(
+        &mut commands,
+        &theme,
+        &font_stack,
+        &asset_server,
+        &mut image_cache,
+    );
+    // Respawned items go on top, this puts them back in their place
+    restack_writer.send(RestackItems(state.order.clone()));
+
+    if state.theme != theme.mode {
+        theme_writer.send(ThemeEvent { theme: state.theme });
+    }
+    history.restoring = true;
+}
+
+/// Items whose style can be edited in place.
+type RestyledFilter = Or<(Changed<Connector>, Changed<ShapeItem>)>;
+
+fn detect_edits(
+    mut history: ResMut<History>,
+    added_query: Query<(), Added<Item>>,
+    mut removed: RemovedComponents<Item>,
+    mut theme_did_change_reader: EventReader<ThemeDidChange>,
+    mut selection_moved_reader: EventReader<SelectionMoved>,
+    mut selection_transformed_reader: EventReader<SelectionTransformed>,
+    // Edits are recorded once they are committed, not per keystroke
+    text_query: Query<(), (Changed<RichText>, Without<TextEditor>)>,
+    frame_query: Query<(), Changed<Frame>>,
+    style_query: Query<(), RestyledFilter>,
+) {
+    if theme_did_change_reader.read().last().is_some() {
+        history.mark(EditAction::Theme);
+    }
+
+    if !removed.is_empty() {
+        removed.clear();
+        history.mark(EditAction::Delete);
+    }
+
+    if !added_query.is_empty() {
+        history.mark(EditAction::Add);
+    }
+
+    // Spawned frames count as changed too, they were marked as added above
+    if !frame_query.is_empty() {
+        history.mark(EditAction::Collapse);
+    }
+
+    if !style_query.is_empty() {
+        history.mark(EditAction::Restyle);
+    }
+
+    // Moves are committed when a drag, resize or rotation ends, or with each nudge
+    let moved = selection_moved_reader.read().last().is_some();
+    let transformed = selection_transformed_reader.read().last().is_some();
+    if moved || transformed {
+        history.mark(EditAction::Move);
+    }
+
+    if !text_query.is_empty() {
+        history.mark(EditAction::Text);
+    }
+}
+
+fn record_history(world: &mut World) {
+    let (pending, rebase) = {
+        let mut history = world.resource_mut::<History>();
+        let pending = history.pending.take();
+        if std::mem::take(&mut history.restoring) {
+            return;
+        }
+
+        (pending, std::mem::take(&mut history.rebase))
+    };
+
+    if pending.is_none() && !rebase {
+        return;
+    }
+
+    let state = BoardState::capture(world);
+    let mut history = world.resource_mut::<History>();
+
+    if rebase {
+        history.current = Some(state);
+    } else if let Some(action) = pending {
+        history.record(action, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, window::WindowThemeChanged};
+
+    use super::*;
+    use crate::{
+        document::ItemKind,
+        events::{AddItemEvent, TxOutputEvent},
+        item::ItemPlugin,
+        theme::ThemePlugin,
+    };
+
+    fn swatch(x: f32) -> ItemDocument {
+        ItemDocument {
+            id: ItemId::new(),
+            position: Vec2::new(x, 0.),
+            z: 0.,
+            size: None,
+            rotation: 0.,
+            kind: ItemKind::Swatch {
+                color: Palette::SKY_200,
+            },
+        }
+    }
+
+    fn state(items: &[ItemDocument]) -> BoardState {
+        BoardState {
+            items: items.iter().map(|item| (item.id, item.clone())).collect(),
+            order: items.iter().map(|item| item.id).collect(),
+            theme: ThemeMode::Light,
+        }
+    }
+
+    /// Records each state in turn, then undoes back to the first and redoes to the last.
+    fn assert_undo_redo(states: &[BoardState]) {
+        let mut history = History {
+            current: Some(states[0].clone()),
+            ..Default::default()
+        };
+        for state in &states[1..] {
+            history.record(EditAction::Move, state.clone());
+        }
+
+        for state in states.iter().rev().skip(1) {
+            assert!(history.undo());
+            assert_eq!(history.current.as_ref(), Some(state));
+        }
+        assert!(!history.undo());
+
+        for state in &states[1..] {
+            assert!(history.redo());
+            assert_eq!(history.current.as_ref(), Some(state));
+        }
+        assert!(!history.redo());
+    }
+
+    #[test]
+    fn entries_only_keep_the_changed_items() {
+        let (a, b, c) = (swatch(0.), swatch(100.), swatch(200.));
+        let moved_b = ItemDocument {
+            position: Vec2::new(100., 50.),
+            ..b.clone()
+        };
+
+        let entry = state(&[a.clone(), b, c.clone()])
+            .diff(EditAction::Move, &state(&[a, moved_b.clone(), c]))
+            .unwrap();
+
+        assert_eq!(entry.changes.len(), 1);
+        assert_eq!(entry.changes[0].after, Some((1, moved_b)));
+        assert!(entry.order.is_none());
+        assert!(entry.theme.is_none());
+    }
+
+    #[test]
+    fn unchanged_boards_are_not_recorded() {
+        let board = state(&[swatch(0.), swatch(100.)]);
+        assert!(board.diff(EditAction::Move, &board.clone()).is_none());
+    }
+
+    #[test]
+    fn adds_deletes_and_restacks_are_undone_in_place() {
+        let (a, b, c, d) = (swatch(0.), swatch(100.), swatch(200.), swatch(300.));
+
+        assert_undo_redo(&[
+            state(&[a.clone(), b.clone(), c.clone()]),
+            // Deleted from the middle
+            state(&[a.clone(), c.clone()]),
+            // Added on top
+            state(&[a.clone(), c.clone(), d.clone()]),
+            // Brought to the front
+            state(&[c.clone(), d.clone(), a.clone()]),
+            // Deleted and added in one edit
+            state(&[b, d, a]),
+        ]);
+    }
+
+    #[test]
+    fn the_oldest_entries_are_dropped() {
+        let mut history = History {
+            current: Some(state(&[])),
+            ..Default::default()
+        };
+        for x in 0..=MAX_HISTORY {
+            history.record(EditAction::Add, state(&[swatch(x as f32)]));
+        }
+
+        assert_eq!(history.undo_stack.len(), MAX_HISTORY);
+    }
+
+    /// A headless app with the item and history plugins, and three swatches on the board.
+    fn app_with_swatches() -> (App, [ItemDocument; 3]) {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ThemePlugin,
+            ItemPlugin,
+            HistoryPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_resource::<FontStack>()
+        .init_resource::<ImageCache>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Keymap>()
+        .insert_resource(TxOutputEvent(crossbeam_channel::unbounded().0))
+        .add_event::<WindowThemeChanged>()
+        .add_event::<ThemeEvent>()
+        .add_event::<LoadBoardEvent>()
+        .add_event::<HistoryEvent>()
+        .add_event::<AddItemEvent>()
+        .add_event::<SelectionMoved>()
+        .add_event::<SelectionTransformed>();
+
+        let swatches = [swatch(0.), swatch(100.), swatch(200.)];
+        let board = BoardDocument {
+            items: swatches
+                .iter()
+                .enumerate()
+                .map(|(z, item)| ItemDocument {
+                    z: z as f32,
+                    ..item.clone()
+                })
+                .collect(),
+        };
+        app.world.run_system_once(
+            move |mut commands: Commands,
+                  theme: Res<Theme>,
+                  font_stack: Res<FontStack>,
+                  asset_server: Res<AssetServer>,
+                  mut image_cache: ResMut<ImageCache>| {
+                board.spawn(
+                    &mut commands,
+                    &theme,
+                    &font_stack,
+                    &asset_server,
+                    &mut image_cache,
+                );
+            },
+        );
+        // Records the board as it was spawned, to undo back to
+        app.update();
+
+        (app, swatches)
+    }
+
+    fn entity(app: &mut App, id: ItemId) -> Option<Entity> {
+        app.world
+            .query::<(Entity, &ItemId)>()
+            .iter(&app.world)
+            .find_map(|(entity, item_id)| (*item_id == id).then_some(entity))
+    }
+
+    /// The ids of the items on the board, from the bottom of the stack to the top.
+    fn stacked_ids(app: &App) -> Vec<ItemId> {
+        BoardDocument::capture(&app.world)
+            .items
+            .iter()
+            .map(|item| item.id)
+            .collect()
+    }
+
+    fn send_and_update(app: &mut App, event: HistoryEvent) {
+        app.world.send_event(event);
+        app.update();
+    }
+
+    #[test]
+    fn undoing_a_delete_only_respawns_the_deleted_item() {
+        let (mut app, [a, b, c]) = app_with_swatches();
+        let (a_entity, c_entity) = (entity(&mut app, a.id), entity(&mut app, c.id));
+
+        let b_entity = entity(&mut app, b.id).unwrap();
+        app.world.entity_mut(b_entity).despawn_recursive();
+        app.update();
+        assert_eq!(stacked_ids(&app), [a.id, c.id]);
+
+        send_and_update(&mut app, HistoryEvent::Undo);
+        assert_eq!(stacked_ids(&app), [a.id, b.id, c.id]);
+        assert_eq!(entity(&mut app, a.id), a_entity);
+        assert_eq!(entity(&mut app, c.id), c_entity);
+        assert_ne!(entity(&mut app, b.id), Some(b_entity));
+
+        send_and_update(&mut app, HistoryEvent::Redo);
+        assert_eq!(stacked_ids(&app), [a.id, c.id]);
+        assert_eq!(entity(&mut app, a.id), a_entity);
+    }
+
+    #[test]
+    fn undoing_a_move_puts_the_item_back() {
+        let (mut app, [a, b, c]) = app_with_swatches();
+        let (a_entity, b_entity) = (entity(&mut app, a.id), entity(&mut app, b.id));
+        let c_entity = entity(&mut app, c.id).unwrap();
+
+        app.world
+            .get_mut::<Transform>(c_entity)
+            .unwrap()
+            .translation += Vec3::new(50., 50., 0.);
+        app.world.send_event(SelectionMoved {
+            entities: vec![c_entity],
+        });
+        app.update();
+
+        send_and_update(&mut app, HistoryEvent::Undo);
+        let board = BoardDocument::capture(&app.world);
+        let restored = board.items.iter().find(|item| item.id == c.id).unwrap();
+        assert!(restored.position.abs_diff_eq(c.position, 1e-4));
+        assert_eq!(stacked_ids(&app), [a.id, b.id, c.id]);
+        assert_eq!(entity(&mut app, a.id), a_entity);
+        assert_eq!(entity(&mut app, b.id), b_entity);
+
+        // The respawn and restack aren't recorded as edits of their own
+        app.update();
+        let history = app.world.resource::<History>();
+        assert!(history.undo_stack.is_empty());
+        assert_eq!(history.redo_stack.len(), 1);
+    }
+}
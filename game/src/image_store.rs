@@ -0,0 +1,148 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::{
+    asset::io::{AssetReader, AssetReaderError, AssetSource, PathStream, Reader, VecReader},
+    utils::{BoxedFuture, HashMap},
+};
+
+use crate::prelude::*;
+
+/// Asset source that serves images out of the [`ImageStore`], e.g. `board://<key>`.
+pub const IMAGE_STORE_SOURCE: &str = "board";
+
+/// Content-addressed image bytes, keyed by their hash and file extension.
+/// The game only reads from it, persisting the bytes is up to the host app.
+#[derive(Resource, Clone, Default)]
+pub struct ImageStore {
+    images: Arc<RwLock<HashMap<String, Arc<Vec<u8>>>>>,
+}
+
+impl ImageStore {
+    /// Adds an image and returns the key it is stored under.
+    /// The extension is kept in the key so Bevy can pick the right image loader.
+    pub fn insert(&self, bytes: Vec<u8>, extension: &str) -> String {
+        let key = format!("{}.{}", blake3::hash(&bytes).to_hex(), extension);
+        self.restore(key.clone(), bytes);
+        key
+    }
+
+    /// Decodes a base64 `data:` URI and adds the image.
+    /// Returns `None` if the URI is malformed or not a supported image type.
+    pub fn insert_data_uri(&self, uri: &str) -> Option<String> {
+        let (header, data) = uri.strip_prefix("data:")?.split_once(',')?;
+        let extension = Self::extension_for_mime(header.strip_suffix(";base64")?)?;
+        let bytes = STANDARD.decode(data).ok()?;
+
+        Some(self.insert(bytes, extension))
+    }
+
+    /// The file extension to store an image under, for the image types the game can load.
+    pub fn extension_for_mime(mime: &str) -> Option<&'static str> {
+        match mime {
+            "image/png" => Some("png"),
+            "image/jpeg" => Some("jpg"),
+            _ => None,
+        }
+    }
+
+    /// Adds an image under a key previously returned by [`ImageStore::insert`].
+    pub fn restore(&self, key: String, bytes: Vec<u8>) {
+        self.images.write().unwrap().insert(key, Arc::new(bytes));
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<Vec<u8>>> {
+        self.images.read().unwrap().get(key).cloned()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.images.read().unwrap().contains_key(key)
+    }
+
+    /// The path to load a stored image through the [`AssetServer`].
+    pub fn asset_path(key: &str) -> String {
+        format!("{IMAGE_STORE_SOURCE}://{key}")
+    }
+
+    /// The inverse of [`ImageStore::asset_path`], `None` for images from other sources.
+    pub fn key_from_asset_path(path: &str) -> Option<&str> {
+        path.strip_prefix(IMAGE_STORE_SOURCE)?.strip_prefix("://")
+    }
+}
+
+impl fmt::Debug for ImageStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageStore")
+            .field("images", &self.images.read().unwrap().len())
+            .finish()
+    }
+}
+
+/// Registers the [`ImageStore`] as an asset source and a resource.
+/// Must be added before `DefaultPlugins`, asset sources are built by the `AssetPlugin`.
+pub(crate) struct ImageStorePlugin {
+    pub store: ImageStore,
+}
+
+impl Plugin for ImageStorePlugin {
+    fn build(&self, app: &mut App) {
+        let store = self.store.clone();
+
+        app.insert_resource(self.store.clone())
+            .register_asset_source(
+                IMAGE_STORE_SOURCE,
+                AssetSource::build().with_reader(move || {
+                    Box::new(ImageStoreReader {
+                        store: store.clone(),
+                    })
+                }),
+            );
+    }
+}
+
+struct ImageStoreReader {
+    store: ImageStore,
+}
+
+impl AssetReader for ImageStoreReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            path.to_str()
+                .and_then(|key| self.store.get(key))
+                .map(|bytes| {
+                    let reader: Box<Reader> = Box::new(VecReader::new(bytes.to_vec()));
+                    reader
+                })
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))
+        })
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        // Stored images never have meta files
+        Box::pin(async move { Err(AssetReaderError::NotFound(path.to_path_buf())) })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(path.to_path_buf())) })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        _path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move { Ok(false) })
+    }
+}
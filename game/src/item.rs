@@ -1,25 +1,169 @@
 #![allow(clippy::type_complexity)]
+use std::fmt;
+
+use bevy::{
+    ecs::query::QueryItem,
+    render::primitives::Aabb,
+    utils::{HashMap, Uuid},
+};
+use serde::{Deserialize, Serialize};
+
 use crate::{
+    color_swatch::ColorSwatch,
+    connector::{Connector, CONNECTOR_Z, CONNECTOR_Z_STEP},
+    drawing::PenStroke,
+    events::{AddItemEvent, ItemType},
+    frame::{Frame, FRAME_Z, FRAME_Z_STEP},
+    group::{stacked_size, Group},
+    keymap::{Action, Keymap},
+    post_it::{ImageItem, PostIt},
     prelude::*,
     select::components::{Selectable, Selected, SelectionBox},
+    shape::ShapeItem,
+    text::CanvasText,
+    text_edit::not_editing_text,
 };
 pub struct ItemPlugin;
 
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ItemCounter>()
-            .add_systems(Update, (increment_item_counter, delete_item));
+            .init_resource::<ItemIndex>()
+            .add_event::<SpawnItemEvent>()
+            .add_event::<RestackItems>()
+            .configure_sets(
+                Update,
+                (ItemDeletion::Delete, ItemDeletion::CleanUp).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    // After the deletions are applied, so items spawned along with them are
+                    // stacked in the same frame
+                    increment_item_counter.after(ItemDeletion::CleanUp),
+                    restack_items.after(increment_item_counter),
+                    delete_item
+                        .run_if(not_editing_text)
+                        .in_set(ItemDeletion::Delete),
+                    // Despawns are only applied at the end of the schedule otherwise
+                    apply_deferred
+                        .after(ItemDeletion::Delete)
+                        .before(ItemDeletion::CleanUp),
+                    place_added_items,
+                ),
+            )
+            .add_systems(PostUpdate, update_item_index);
     }
 }
 
 #[derive(Resource, Default)]
 pub struct ItemCounter {
     count: f32,
+    /// Frames stack among themselves, below every other item.
+    frames: f32,
+    /// Connectors stack among themselves, between frames and the other items.
+    connectors: f32,
 }
 
 #[derive(Component)]
 pub struct Deletable;
 
+/// Deleting items, then whatever depended on them, like the connectors attached to them.
+/// The deletions are applied in between, so both happen in one frame and are undone together.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ItemDeletion {
+    Delete,
+    CleanUp,
+}
+
+/// An item to spawn at `position` in world coordinates.
+/// Without a position, each item type picks its own default spot.
+#[derive(Event, Clone, Debug)]
+pub(crate) struct SpawnItemEvent {
+    pub item: AddItemEvent,
+    pub position: Option<Vec2>,
+}
+
+/// Restacks items into this order of their ids, from the bottom up, e.g. after an undo
+/// respawned some of them. Items below the first one out of place keep their z.
+#[derive(Event, Clone, Debug)]
+pub(crate) struct RestackItems(pub Vec<ItemId>);
+
+/// Identifies an item across saves, undo and the web app, unlike its `Entity`.
+#[derive(
+    Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub struct ItemId(Uuid);
+
+impl ItemId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for ItemId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The box size of items drawn as shapes, like post-its, swatches and images.
+/// Each item type redraws its shapes when it changes.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ItemSize(pub Vec2);
+
+/// An item drawn to fill its [`ItemSize`], along with the children sized with it.
+pub(crate) type ItemOutlineQuery = (
+    Entity,
+    &'static ItemSize,
+    &'static Children,
+    &'static mut Path,
+);
+
+/// Bounds for an item whose shape changed size, they are only calculated once by bevy.
+pub(crate) fn item_aabb(size: Vec2) -> Aabb {
+    let half_extents = (size / 2.).extend(0.);
+    Aabb::from_min_max(-half_extents, half_extents)
+}
+
+/// Finds the entity of an item by its [`ItemId`], and the other way around.
+/// Updated at the end of every frame.
+#[derive(Resource, Default, Debug)]
+pub struct ItemIndex {
+    entities: HashMap<ItemId, Entity>,
+    ids: HashMap<Entity, ItemId>,
+}
+
+impl ItemIndex {
+    pub fn entity(&self, id: ItemId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    /// Still answers for items despawned this frame, until the index is updated.
+    pub fn id(&self, entity: Entity) -> Option<ItemId> {
+        self.ids.get(&entity).copied()
+    }
+
+    fn insert(&mut self, id: ItemId, entity: Entity) {
+        if let Some(previous) = self.ids.insert(entity, id) {
+            self.entities.remove(&previous);
+        }
+        self.entities.insert(id, entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(id) = self.ids.remove(&entity) {
+            self.entities.remove(&id);
+        }
+    }
+}
+
 impl ItemCounter {
     pub fn count(&self) -> f32 {
         self.count
@@ -28,11 +172,22 @@ impl ItemCounter {
     fn increment(&mut self) {
         self.count += 1.0;
     }
+
+    fn next_frame_z(&mut self) -> f32 {
+        self.frames += 1.0;
+        FRAME_Z + self.frames * FRAME_Z_STEP
+    }
+
+    fn next_connector_z(&mut self) -> f32 {
+        self.connectors += 1.0;
+        CONNECTOR_Z + self.connectors * CONNECTOR_Z_STEP
+    }
 }
 
 #[derive(Bundle)]
 pub struct ItemBundle {
     pub item: Item,
+    pub id: ItemId,
     pub selectable: Selectable,
     pub fill: Fill,
     pub stroke: Stroke,
@@ -44,6 +199,7 @@ impl Default for ItemBundle {
     fn default() -> Self {
         Self {
             item: Item,
+            id: ItemId::new(),
             selectable: Selectable,
             deletable: Deletable,
             fill: Fill::color(Palette::WHITE),
@@ -58,18 +214,88 @@ impl Default for ItemBundle {
 #[derive(Component)]
 pub struct Item;
 
+/// The marker components telling the item types apart.
+pub(crate) type ItemTypeQuery = (
+    Has<PostIt>,
+    Has<CanvasText>,
+    Has<ColorSwatch>,
+    Has<ImageItem>,
+    Has<Group>,
+    Has<Frame>,
+    Has<Connector>,
+    Has<PenStroke>,
+    Has<ShapeItem>,
+);
+
+pub(crate) fn item_type(types: QueryItem<ItemTypeQuery>) -> Option<ItemType> {
+    let (
+        is_post_it,
+        is_text,
+        is_swatch,
+        is_image,
+        is_group,
+        is_frame,
+        is_connector,
+        is_drawing,
+        is_shape,
+    ) = types;
+    [
+        (is_post_it, ItemType::PostIt),
+        (is_text, ItemType::Text),
+        (is_swatch, ItemType::Swatch),
+        (is_image, ItemType::Image),
+        (is_group, ItemType::Group),
+        (is_frame, ItemType::Frame),
+        (is_connector, ItemType::Connector),
+        (is_drawing, ItemType::Drawing),
+        (is_shape, ItemType::Shape),
+    ]
+    .into_iter()
+    .find_map(|(is_type, item_type)| is_type.then_some(item_type))
+}
+
 fn increment_item_counter(
     mut item_counter: ResMut<ItemCounter>,
-    mut query: Query<(Entity, &mut Transform), Or<(Added<Item>, Added<Selected>)>>,
+    // Group members are stacked inside their group instead
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            Ref<Item>,
+            Has<Frame>,
+            Has<Connector>,
+        ),
+        (Or<(Added<Item>, Added<Selected>)>, Without<Parent>),
+    >,
     selected_query: Query<Entity, &Selected>,
     selection_box_query: Query<&SelectionBox>,
+    group_query: Query<&Children, With<Group>>,
 ) {
     let selected_count = selected_query.iter().count();
-    for (entity, mut transform) in &mut query {
+
+    // Items spawned together keep the stacking order they were spawned with
+    let mut items = query.iter_mut().collect::<Vec<_>>();
+    items.sort_by(|(_, a, ..), (_, b, ..)| a.translation.z.total_cmp(&b.translation.z));
+
+    for (entity, mut transform, item, is_frame, is_connector) in items {
+        // Frames and connectors stay below the items they hold and connect, selecting one
+        // doesn't bring it to the front
+        if is_frame || is_connector {
+            if item.is_added() {
+                transform.translation.z = if is_frame {
+                    item_counter.next_frame_z()
+                } else {
+                    item_counter.next_connector_z()
+                };
+            }
+            continue;
+        }
+
         let is_selected = selected_query.get(entity).is_ok();
         let is_selecting = !selection_box_query.is_empty();
 
-        if (is_selected && selected_count > 1) || is_selecting {
+        // New items always go on top, even when they are spawned selected
+        if !item.is_added() && ((is_selected && selected_count > 1) || is_selecting) {
             // Only increment if there is only one selected item
             // This indicates that the item was just selected, and should
             // be brought to the front
@@ -78,17 +304,110 @@ fn increment_item_counter(
 
         item_counter.increment();
         transform.translation.z = item_counter.count();
+        // Leave room for the members of a group above it
+        for _ in 1..stacked_size(entity, &group_query) {
+            item_counter.increment();
+        }
     }
 }
 
-fn delete_item(
+fn restack_items(
+    mut events: EventReader<RestackItems>,
+    mut item_counter: ResMut<ItemCounter>,
+    mut item_query: Query<
+        (Entity, &ItemId, &mut Transform, Has<Frame>, Has<Connector>),
+        (With<Item>, Without<Parent>),
+    >,
+    group_query: Query<&Children, With<Group>>,
+) {
+    let Some(RestackItems(order)) = events.read().last() else {
+        return;
+    };
+
+    let items = item_query
+        .iter()
+        .map(|(entity, id, transform, is_frame, is_connector)| {
+            (
+                *id,
+                (entity, transform.translation.z, (is_frame, is_connector)),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Frames, connectors and the other items each stack among themselves
+    for band in [(false, false), (true, false), (false, true)] {
+        let target = order
+            .iter()
+            .filter_map(|id| items.get(id))
+            .filter(|(.., item_band)| *item_band == band)
+            .map(|(entity, z, _)| (*entity, *z))
+            .collect::<Vec<_>>();
+        let mut current = target.clone();
+        current.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let Some(first) = target
+            .iter()
+            .zip(&current)
+            .position(|((a, _), (b, _))| a != b)
+        else {
+            continue;
+        };
+
+        for (entity, _) in &target[first..] {
+            let Ok((.., mut transform, _, _)) = item_query.get_mut(*entity) else {
+                continue;
+            };
+            transform.translation.z = match band {
+                (true, _) => item_counter.next_frame_z(),
+                (_, true) => item_counter.next_connector_z(),
+                _ => {
+                    item_counter.increment();
+                    let z = item_counter.count();
+                    for _ in 1..stacked_size(*entity, &group_query) {
+                        item_counter.increment();
+                    }
+                    z
+                }
+            };
+        }
+    }
+}
+
+pub(crate) fn delete_item(
     mut commands: Commands,
     selected_query: Query<Entity, With<Selected>>,
     keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
 ) {
-    if keys.just_pressed(KeyCode::Back) {
+    if keymap.just_pressed(Action::Delete, &keys) {
         for entity in selected_query.iter() {
             commands.entity(entity).despawn_recursive();
         }
     }
 }
+
+fn place_added_items(
+    mut events: EventReader<AddItemEvent>,
+    mut spawn_item_writer: EventWriter<SpawnItemEvent>,
+) {
+    for event in events.read() {
+        spawn_item_writer.send(SpawnItemEvent {
+            item: event.clone(),
+            position: None,
+        });
+    }
+}
+
+pub(crate) fn update_item_index(
+    mut item_index: ResMut<ItemIndex>,
+    changed_query: Query<(Entity, &ItemId), Changed<ItemId>>,
+    mut removed: RemovedComponents<ItemId>,
+) {
+    for entity in removed.read() {
+        item_index.remove(entity);
+    }
+
+    for (entity, id) in &changed_query {
+        item_index.insert(*id, entity);
+    }
+}
@@ -0,0 +1,674 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{KeymapEvent, OutputEvent, TxOutputEvent},
+    prelude::*,
+    text_edit::not_editing_text,
+};
+
+pub struct KeymapPlugin;
+
+impl Plugin for KeymapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keymap>().add_systems(
+            Update,
+            (
+                set_keymap,
+                show_shortcuts.run_if(not_editing_text).after(set_keymap),
+            ),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Undo,
+    Redo,
+    Copy,
+    Cut,
+    /// Handled by the web app, which has to read the system clipboard in the key press.
+    Paste,
+    Duplicate,
+    Delete,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    NudgeLeftLarge,
+    NudgeRightLarge,
+    NudgeUpLarge,
+    NudgeDownLarge,
+    Group,
+    Ungroup,
+    Connect,
+    SelectAll,
+    SelectSimilarColor,
+    InvertSelection,
+    SelectPostIts,
+    SelectText,
+    SelectSwatches,
+    SelectImages,
+    ToggleLasso,
+    ExitGroup,
+    Pen,
+    Highlighter,
+    Eraser,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    ShowShortcuts,
+}
+
+impl Action {
+    /// Every action, in the order they are listed in.
+    pub const ALL: [Action; 34] = [
+        Action::Undo,
+        Action::Redo,
+        Action::Copy,
+        Action::Cut,
+        Action::Paste,
+        Action::Duplicate,
+        Action::Delete,
+        Action::NudgeLeft,
+        Action::NudgeRight,
+        Action::NudgeUp,
+        Action::NudgeDown,
+        Action::NudgeLeftLarge,
+        Action::NudgeRightLarge,
+        Action::NudgeUpLarge,
+        Action::NudgeDownLarge,
+        Action::Group,
+        Action::Ungroup,
+        Action::Connect,
+        Action::SelectAll,
+        Action::SelectSimilarColor,
+        Action::InvertSelection,
+        Action::SelectPostIts,
+        Action::SelectText,
+        Action::SelectSwatches,
+        Action::SelectImages,
+        Action::ToggleLasso,
+        Action::ExitGroup,
+        Action::Pen,
+        Action::Highlighter,
+        Action::Eraser,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetZoom,
+        Action::ShowShortcuts,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Copy => "Copy",
+            Action::Cut => "Cut",
+            Action::Paste => "Paste",
+            Action::Duplicate => "Duplicate",
+            Action::Delete => "Delete",
+            Action::NudgeLeft => "Nudge left",
+            Action::NudgeRight => "Nudge right",
+            Action::NudgeUp => "Nudge up",
+            Action::NudgeDown => "Nudge down",
+            Action::NudgeLeftLarge => "Nudge left by 10",
+            Action::NudgeRightLarge => "Nudge right by 10",
+            Action::NudgeUpLarge => "Nudge up by 10",
+            Action::NudgeDownLarge => "Nudge down by 10",
+            Action::Group => "Group",
+            Action::Ungroup => "Ungroup",
+            Action::Connect => "Connect",
+            Action::SelectAll => "Select all",
+            Action::SelectSimilarColor => "Select similar color",
+            Action::InvertSelection => "Invert selection",
+            Action::SelectPostIts => "Select all post-its",
+            Action::SelectText => "Select all text",
+            Action::SelectSwatches => "Select all swatches",
+            Action::SelectImages => "Select all images",
+            Action::ToggleLasso => "Toggle lasso",
+            Action::ExitGroup => "Leave group",
+            Action::Pen => "Pen",
+            Action::Highlighter => "Highlighter",
+            Action::Eraser => "Eraser",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::ResetZoom => "Zoom to 100%",
+            Action::ShowShortcuts => "Show shortcuts",
+        }
+    }
+
+    /// The heading the action is listed under.
+    pub fn group(&self) -> &'static str {
+        match self {
+            Action::Undo
+            | Action::Redo
+            | Action::Copy
+            | Action::Cut
+            | Action::Paste
+            | Action::Duplicate
+            | Action::Delete => "Edit",
+            Action::NudgeLeft
+            | Action::NudgeRight
+            | Action::NudgeUp
+            | Action::NudgeDown
+            | Action::NudgeLeftLarge
+            | Action::NudgeRightLarge
+            | Action::NudgeUpLarge
+            | Action::NudgeDownLarge
+            | Action::Group
+            | Action::Ungroup
+            | Action::Connect => "Arrange",
+            Action::SelectAll
+            | Action::SelectSimilarColor
+            | Action::InvertSelection
+            | Action::SelectPostIts
+            | Action::SelectText
+            | Action::SelectSwatches
+            | Action::SelectImages
+            | Action::ToggleLasso
+            | Action::ExitGroup => "Selection",
+            Action::Pen | Action::Highlighter | Action::Eraser => "Draw",
+            Action::ZoomIn | Action::ZoomOut | Action::ResetZoom | Action::ShowShortcuts => "View",
+        }
+    }
+
+    /// Browser shortcuts like Ctrl+K and Ctrl+Shift+I are left alone, some of them can't be
+    /// taken over by a page. Ctrl+D can, the web app keeps it from bookmarking the page.
+    fn default_chords(&self) -> Vec<KeyChord> {
+        let command = |key| KeyChord::new(key).command();
+        let alt = |key| KeyChord::new(key).alt();
+
+        match self {
+            Action::Undo => vec![command(KeyCode::Z)],
+            Action::Redo => vec![command(KeyCode::Z).shift(), command(KeyCode::Y)],
+            Action::Copy => vec![command(KeyCode::C)],
+            Action::Cut => vec![command(KeyCode::X)],
+            Action::Paste => vec![command(KeyCode::V)],
+            Action::Duplicate => vec![command(KeyCode::D)],
+            Action::Delete => vec![KeyChord::new(KeyCode::Delete), KeyChord::new(KeyCode::Back)],
+            Action::NudgeLeft => vec![KeyChord::new(KeyCode::Left)],
+            Action::NudgeRight => vec![KeyChord::new(KeyCode::Right)],
+            Action::NudgeUp => vec![KeyChord::new(KeyCode::Up)],
+            Action::NudgeDown => vec![KeyChord::new(KeyCode::Down)],
+            Action::NudgeLeftLarge => vec![KeyChord::new(KeyCode::Left).shift()],
+            Action::NudgeRightLarge => vec![KeyChord::new(KeyCode::Right).shift()],
+            Action::NudgeUpLarge => vec![KeyChord::new(KeyCode::Up).shift()],
+            Action::NudgeDownLarge => vec![KeyChord::new(KeyCode::Down).shift()],
+            Action::Group => vec![command(KeyCode::G)],
+            Action::Ungroup => vec![command(KeyCode::G).shift()],
+            Action::Connect => vec![KeyChord::new(KeyCode::C)],
+            Action::SelectAll => vec![command(KeyCode::A)],
+            Action::SelectSimilarColor => vec![command(KeyCode::A).alt()],
+            Action::InvertSelection => vec![alt(KeyCode::I)],
+            Action::SelectPostIts => vec![alt(KeyCode::Key1)],
+            Action::SelectText => vec![alt(KeyCode::Key2)],
+            Action::SelectSwatches => vec![alt(KeyCode::Key3)],
+            Action::SelectImages => vec![alt(KeyCode::Key4)],
+            Action::ToggleLasso => vec![KeyChord::new(KeyCode::L)],
+            Action::ExitGroup => vec![KeyChord::new(KeyCode::Escape)],
+            Action::Pen => vec![KeyChord::new(KeyCode::P)],
+            Action::Highlighter => vec![KeyChord::new(KeyCode::H)],
+            Action::Eraser => vec![KeyChord::new(KeyCode::E)],
+            Action::ZoomIn => vec![
+                KeyChord::new(KeyCode::Equals),
+                KeyChord::new(KeyCode::Equals).shift(),
+            ],
+            Action::ZoomOut => vec![KeyChord::new(KeyCode::Minus)],
+            Action::ResetZoom => vec![KeyChord::new(KeyCode::Key0).shift()],
+            Action::ShowShortcuts => vec![KeyChord::new(KeyCode::Slash).shift()],
+        }
+    }
+}
+
+/// A key with the modifiers that have to be held with it.
+/// `command` is Cmd on macOS and Ctrl elsewhere, either is accepted on any platform.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+/// Names of the keys chords can use, as written in overrides.
+const KEY_NAMES: [(KeyCode, &str); 58] = [
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Return, "Enter"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equals, "="),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Apostrophe, "'"),
+];
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            command: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn command(self) -> Self {
+        Self {
+            command: true,
+            ..self
+        }
+    }
+
+    pub fn shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+
+    pub fn alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    /// Whether the key was just pressed with exactly these modifiers held.
+    fn just_pressed(&self, keys: &Input<KeyCode>) -> bool {
+        keys.just_pressed(self.key)
+            && self.command
+                == keys.any_pressed([
+                    KeyCode::ControlLeft,
+                    KeyCode::ControlRight,
+                    KeyCode::SuperLeft,
+                    KeyCode::SuperRight,
+                ])
+            && self.shift == keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            && self.alt == keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+    }
+
+    /// Whether a key pressed in the browser is this chord. `code` is the physical key, as in
+    /// `KeyboardEvent.code`, which is also what bevy reads keys from on the web.
+    fn matches_web_key(&self, code: &str, command: bool, shift: bool, alt: bool) -> bool {
+        self.web_code() == code && self.command == command && self.shift == shift && self.alt == alt
+    }
+
+    /// The key's `KeyboardEvent.code`, like `KeyV`, `Digit1` or `ArrowUp`.
+    fn web_code(&self) -> String {
+        let name = self.key_name();
+        match self.key {
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => format!("Arrow{name}"),
+            KeyCode::Minus => "Minus".to_string(),
+            KeyCode::Equals => "Equal".to_string(),
+            KeyCode::Slash => "Slash".to_string(),
+            KeyCode::Comma => "Comma".to_string(),
+            KeyCode::Period => "Period".to_string(),
+            KeyCode::Semicolon => "Semicolon".to_string(),
+            KeyCode::Apostrophe => "Quote".to_string(),
+            _ if name.len() == 1 && name.as_bytes()[0].is_ascii_digit() => format!("Digit{name}"),
+            _ if name.len() == 1 => format!("Key{name}"),
+            _ => name.to_string(),
+        }
+    }
+
+    fn key_name(&self) -> &'static str {
+        KEY_NAMES
+            .iter()
+            .find(|(key, _)| *key == self.key)
+            .map_or("?", |(_, name)| name)
+    }
+
+    /// How the chord is shown to users, with macOS symbols or Windows and Linux names.
+    pub fn label(&self, mac: bool) -> String {
+        if mac {
+            let key = match self.key {
+                KeyCode::Back => "⌫",
+                KeyCode::Delete => "⌦",
+                KeyCode::Return => "↩",
+                _ => self.key_name(),
+            };
+            return format!(
+                "{}{}{}{key}",
+                if self.command { "⌘" } else { "" },
+                if self.alt { "⌥" } else { "" },
+                if self.shift { "⇧" } else { "" },
+            );
+        }
+
+        let mut parts = Vec::new();
+        if self.command {
+            parts.push("Ctrl");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        parts.push(self.key_name());
+        parts.join("+")
+    }
+}
+
+/// Written like `Mod+Shift+Z`, where `Mod` is Cmd or Ctrl.
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.command {
+            write!(f, "Mod+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.key_name())
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parses chords like `Mod+Shift+Z`. `Ctrl`, `Cmd` and `Meta` are accepted for `Mod`, and
+    /// names are case insensitive.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = text.rsplit_once('+').unwrap_or(("", text));
+
+        let key = KEY_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(key.trim()))
+            .map(|(key, _)| *key)
+            .ok_or_else(|| format!("unknown key \"{key}\" in \"{text}\""))?;
+        let mut chord = KeyChord::new(key);
+
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier.trim().to_ascii_lowercase().as_str() {
+                "mod" | "ctrl" | "control" | "cmd" | "command" | "meta" | "super" => {
+                    chord.command = true;
+                }
+                "shift" => chord.shift = true,
+                "alt" | "option" => chord.alt = true,
+                other => return Err(format!("unknown modifier \"{other}\" in \"{text}\"")),
+            }
+        }
+
+        Ok(chord)
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+/// The chords bound to each action.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyChord>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_chords()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Rebinds the actions in a JSON object of action names to chords, like
+    /// `{"delete": ["Delete"], "redo": ["Mod+Shift+Z"]}`.
+    /// Actions it leaves out keep their chords, an empty list unbinds one.
+    pub fn apply_overrides(&mut self, json: &str) -> serde_json::Result<()> {
+        let overrides: HashMap<Action, Vec<KeyChord>> = serde_json::from_str(json)?;
+
+        for (action, chords) in &mut self.bindings {
+            if let Some(overridden) = overrides.get(action) {
+                *chords = overridden.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn chords(&self, action: Action) -> &[KeyChord] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, chords)| chords.as_slice())
+    }
+
+    /// Every action with its chords, in the order of [`Action::ALL`].
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, &[KeyChord])> {
+        self.bindings
+            .iter()
+            .map(|(action, chords)| (*action, chords.as_slice()))
+    }
+
+    /// Whether a key pressed in the browser triggers the action, for shortcuts the web app
+    /// handles itself. `code` is the event's `KeyboardEvent.code`, `command` is Ctrl or Cmd.
+    pub fn matches_web_key(
+        &self,
+        action: Action,
+        code: &str,
+        command: bool,
+        shift: bool,
+        alt: bool,
+    ) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| chord.matches_web_key(code, command, shift, alt))
+    }
+
+    pub(crate) fn just_pressed(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| chord.just_pressed(keys))
+    }
+}
+
+fn set_keymap(mut events: EventReader<KeymapEvent>, mut keymap: ResMut<Keymap>) {
+    if let Some(event) = events.read().last() {
+        *keymap = event.keymap.clone();
+    }
+}
+
+/// The cheat sheet is shown by the web app.
+fn show_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    tx_output_event: Res<TxOutputEvent>,
+) {
+    if keymap.just_pressed(Action::ShowShortcuts, &keys) {
+        tx_output_event.emit(OutputEvent::ShowShortcuts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn every_default_chord_round_trips_through_text() {
+        for (action, chords) in Keymap::default().bindings() {
+            for chord in chords {
+                assert_eq!(chord.to_string().parse(), Ok(*chord), "{action:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn chords_are_parsed_with_any_command_name_and_case() {
+        let redo = KeyChord::new(KeyCode::Z).command().shift();
+
+        for text in ["Mod+Shift+Z", "ctrl+shift+z", "Cmd+Shift+Z", "SHIFT+META+z"] {
+            assert_eq!(chord(text), redo, "{text}");
+        }
+        assert_eq!(redo.to_string(), "Mod+Shift+Z");
+        assert_eq!(chord("Alt+1"), KeyChord::new(KeyCode::Key1).alt());
+        assert_eq!(chord("Option+/"), KeyChord::new(KeyCode::Slash).alt());
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_are_errors() {
+        assert!("Mod+Nope".parse::<KeyChord>().is_err());
+        assert!("Hyper+Z".parse::<KeyChord>().is_err());
+        assert!("".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn overrides_rebind_and_unbind_actions() {
+        let mut keymap = Keymap::default();
+        keymap
+            .apply_overrides(r#"{"delete": ["X"], "redo": []}"#)
+            .unwrap();
+
+        assert_eq!(keymap.chords(Action::Delete), [KeyChord::new(KeyCode::X)]);
+        assert!(keymap.chords(Action::Redo).is_empty());
+        assert_eq!(
+            keymap.chords(Action::Undo),
+            Keymap::default().chords(Action::Undo)
+        );
+    }
+
+    #[test]
+    fn invalid_overrides_leave_the_keymap_as_it_was() {
+        for json in [
+            "not json",
+            r#"{"delete": "Delete"}"#,
+            r#"{"delete": ["Mod+Nope"]}"#,
+            r#"{"launch_rockets": ["R"]}"#,
+        ] {
+            let mut keymap = Keymap::default();
+            assert!(keymap.apply_overrides(json).is_err(), "{json}");
+            assert_eq!(keymap, Keymap::default(), "{json}");
+        }
+    }
+
+    #[test]
+    fn web_keys_match_with_exactly_the_chord_modifiers() {
+        let keymap = Keymap::default();
+
+        assert!(keymap.matches_web_key(Action::Paste, "KeyV", true, false, false));
+        assert!(!keymap.matches_web_key(Action::Paste, "KeyV", true, true, false));
+        assert!(!keymap.matches_web_key(Action::Paste, "KeyV", false, false, false));
+        assert!(!keymap.matches_web_key(Action::Paste, "KeyC", true, false, false));
+        assert!(keymap.matches_web_key(Action::Duplicate, "KeyD", true, false, false));
+
+        assert!(keymap.matches_web_key(Action::NudgeLeft, "ArrowLeft", false, false, false));
+        assert!(keymap.matches_web_key(Action::SelectPostIts, "Digit1", false, false, true));
+        assert!(keymap.matches_web_key(Action::ShowShortcuts, "Slash", false, true, false));
+    }
+
+    #[test]
+    fn command_chords_are_pressed_with_ctrl_or_cmd() {
+        let keymap = Keymap::default();
+
+        for modifier in [
+            KeyCode::ControlLeft,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ] {
+            let mut keys = Input::default();
+            keys.press(modifier);
+            keys.press(KeyCode::Z);
+            assert!(keymap.just_pressed(Action::Undo, &keys), "{modifier:?}");
+            assert!(!keymap.just_pressed(Action::Redo, &keys), "{modifier:?}");
+        }
+
+        let mut keys = Input::default();
+        keys.press(KeyCode::Z);
+        assert!(!keymap.just_pressed(Action::Undo, &keys));
+    }
+
+    #[test]
+    fn defaults_leave_browser_shortcuts_alone() {
+        // Mod+D is taken over for duplicating
+        let reserved = [
+            "Mod+K",
+            "Mod+L",
+            "Mod+N",
+            "Mod+T",
+            "Mod+W",
+            "Mod+Shift+I",
+            "Mod+Shift+J",
+            "Mod+Shift+N",
+            "Mod+Shift+T",
+            "Alt+D",
+        ]
+        .map(chord);
+
+        for (action, chords) in Keymap::default().bindings() {
+            for chord in chords {
+                assert!(!reserved.contains(chord), "{action:?} is bound to {chord}");
+            }
+        }
+    }
+}
@@ -1,14 +1,28 @@
 mod camera;
 mod canvas;
+mod clipboard;
 mod color_swatch;
+mod connector;
 #[cfg(any(feature = "debug", rust_analyzer))]
 mod debug;
+pub mod document;
+mod drawing;
 pub mod events;
+mod frame;
+mod group;
+mod history;
+pub mod image_store;
 mod item;
+pub mod keymap;
+mod markdown;
+mod output;
 mod post_it;
 pub mod prelude;
+pub mod rich_text;
 mod select;
+mod shape;
 mod text;
+mod text_edit;
 pub mod theme;
 mod ui;
 
@@ -21,28 +35,48 @@ use bevy::{
 };
 use camera::CameraPlugin;
 use canvas::CanvasPlugin;
+use clipboard::ClipboardPlugin;
 
-use color_swatch::{spawn_swatch, ColorSwatchPlugin, SWATCH_COLORS};
+use color_swatch::ColorSwatchPlugin;
+use connector::ConnectorPlugin;
 #[cfg(any(feature = "debug", rust_analyzer))]
 use debug::DebugPlugin;
+use document::DocumentPlugin;
+use drawing::DrawingPlugin;
 use events::{Shared, SharedState};
+use frame::FramePlugin;
+use group::GroupPlugin;
+use history::HistoryPlugin;
+use image_store::ImageStorePlugin;
 use item::ItemPlugin;
-use post_it::{spawn_image, spawn_post_it, PostItPlugin};
+use keymap::KeymapPlugin;
+use output::OutputPlugin;
+use post_it::PostItPlugin;
 use prelude::*;
-use rand::seq::SliceRandom;
+use rich_text::RichTextPlugin;
 use select::SelectPlugin;
-use text::{spawn_text, TextPlugin};
-use theme::{Theme, ThemePlugin};
+use serde::{Deserialize, Serialize};
+use shape::ShapeItemPlugin;
+use text::TextPlugin;
+use text_edit::TextEditPlugin;
+use theme::ThemePlugin;
 use ui::UiPlugin;
 
+pub use item::ItemId;
+
 pub fn run(event_plugin: impl Plugin, shared_state: Shared<SharedState>) {
-    let size = shared_state.lock().unwrap().window_size;
+    let (size, image_store) = {
+        let shared_state = shared_state.lock().unwrap();
+        (shared_state.window_size, shared_state.image_store.clone())
+    };
 
     App::new()
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(AssetMetaCheck::Never)
         .init_resource::<ImageCache>()
         .add_plugins((
+            // Asset sources have to be registered before the `AssetPlugin`
+            ImageStorePlugin { store: image_store },
             DefaultPlugins.set(WindowPlugin {
                 primary_window: Some(Window {
                     canvas: Some("#bevy".to_string()),
@@ -66,6 +100,20 @@ pub fn run(event_plugin: impl Plugin, shared_state: Shared<SharedState>) {
             ItemPlugin,
             SelectPlugin,
         ))
+        .add_plugins((
+            DocumentPlugin,
+            HistoryPlugin,
+            OutputPlugin,
+            ClipboardPlugin,
+            TextEditPlugin,
+            RichTextPlugin,
+            KeymapPlugin,
+            GroupPlugin,
+            FramePlugin,
+            ConnectorPlugin,
+            DrawingPlugin,
+            ShapeItemPlugin,
+        ))
         .add_systems(Startup, startup)
         .insert_resource(SharedResource(shared_state))
         .init_resource::<FontStack>()
@@ -91,7 +139,43 @@ struct FontFamily {
     italic: Option<Handle<Font>>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FontFamilyKind {
+    Title,
+    #[default]
+    Body,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FontVariant {
+    #[default]
+    Regular,
+    Bold,
+    Italic,
+}
+
+impl FontStack {
+    pub fn family(&self, kind: FontFamilyKind) -> &FontFamily {
+        match kind {
+            FontFamilyKind::Title => &self.title,
+            FontFamilyKind::Body => &self.body,
+        }
+    }
+
+    pub fn font(&self, kind: FontFamilyKind, variant: FontVariant) -> Handle<Font> {
+        self.family(kind).variant(variant)
+    }
+}
+
 impl FontFamily {
+    pub fn variant(&self, variant: FontVariant) -> Handle<Font> {
+        match variant {
+            FontVariant::Regular => self.regular(),
+            FontVariant::Bold => self.bold(),
+            FontVariant::Italic => self.italic(),
+        }
+    }
+
     pub fn regular(&self) -> Handle<Font> {
         self.regular.clone()
     }
@@ -130,6 +214,64 @@ impl Default for FontSizeMap {
     }
 }
 
+/// A step in the [`FontSizeMap`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FontSize {
+    XXSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    XXLarge,
+}
+
+impl FontSize {
+    const ALL: [FontSize; 7] = [
+        FontSize::XXSmall,
+        FontSize::XSmall,
+        FontSize::Small,
+        FontSize::Medium,
+        FontSize::Large,
+        FontSize::XLarge,
+        FontSize::XXLarge,
+    ];
+
+    pub fn larger(self) -> Self {
+        Self::ALL[(self as usize + 1).min(Self::ALL.len() - 1)]
+    }
+
+    pub fn smaller(self) -> Self {
+        Self::ALL[(self as usize).saturating_sub(1)]
+    }
+}
+
+impl FontSizeMap {
+    pub fn get(&self, size: FontSize) -> f32 {
+        match size {
+            FontSize::XXSmall => self.xxsmall,
+            FontSize::XSmall => self.xsmall,
+            FontSize::Small => self.small,
+            FontSize::Medium => self.medium,
+            FontSize::Large => self.large,
+            FontSize::XLarge => self.xlarge,
+            FontSize::XXLarge => self.xxlarge,
+        }
+    }
+
+    /// The step closest to `font_size`, for stepping from sizes that aren't in the map.
+    pub fn nearest(&self, font_size: f32) -> FontSize {
+        FontSize::ALL
+            .into_iter()
+            .min_by(|a, b| {
+                (self.get(*a) - font_size)
+                    .abs()
+                    .total_cmp(&(self.get(*b) - font_size).abs())
+            })
+            .unwrap_or(FontSize::Medium)
+    }
+}
+
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
 struct CustomMaterial {}
 
@@ -141,13 +283,7 @@ impl Material2d for CustomMaterial {
 #[derive(Resource)]
 pub struct SharedResource(Shared<SharedState>);
 
-fn startup(
-    mut commands: Commands,
-    theme: Res<Theme>,
-    mut font_stack: ResMut<FontStack>,
-    asset_server: Res<AssetServer>,
-    mut image_cache: ResMut<ImageCache>,
-) {
+fn startup(mut font_stack: ResMut<FontStack>, asset_server: Res<AssetServer>) {
     let playfair = FontFamily {
         regular: asset_server.load("fonts/playfair/PlayfairDisplay-Regular.ttf"),
         bold: Some(asset_server.load("fonts/playfair/PlayfairDisplay-Bold.ttf")),
@@ -162,81 +298,4 @@ fn startup(
 
     font_stack.title = playfair;
     font_stack.body = source_sans;
-    let mut swatch_colors = SWATCH_COLORS.choose_multiple(&mut rand::thread_rng(), 4);
-
-    spawn_post_it(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(-500., 200., 0.0),
-        "This is a Post-It. \n\nYou can add more by clicking the chat bubble icon above.",
-    );
-
-    spawn_post_it(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(-64., -87., 0.0),
-        "You can also add color swatches and text boxes.",
-    );
-
-    spawn_swatch(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(-150., 283., 0.0),
-        swatch_colors.next().unwrap(),
-    );
-
-    spawn_swatch(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(286., -2., 0.0),
-        swatch_colors.next().unwrap(),
-    );
-
-    spawn_swatch(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(535., -2., 0.0),
-        swatch_colors.next().unwrap(),
-    );
-
-    spawn_swatch(
-        &mut commands,
-        &theme,
-        &font_stack,
-        Vec3::new(-408., -170., 0.0),
-        swatch_colors.next().unwrap(),
-    );
-
-    spawn_text(
-        &mut commands,
-        &theme,
-        Vec3::new(400., 370., 0.0),
-        "An Example Moodboard",
-        font_stack.size.xlarge,
-        font_stack.title.bold(),
-    );
-
-    spawn_text(
-        &mut commands,
-        &theme,
-        Vec3::new(257., 280., 0.0),
-        "Try dragging things around!",
-        font_stack.size.large,
-        font_stack.body.italic(),
-    );
-
-    let image = asset_server.load("images/night_lights.jpg");
-
-    spawn_image(
-        &mut commands,
-        &theme,
-        Vec3::new(576., -424., 0.0),
-        image,
-        &mut image_cache,
-    );
 }
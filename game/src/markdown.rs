@@ -0,0 +1,438 @@
+use std::ops::Range;
+
+use crate::{
+    document::FontDocument,
+    prelude::*,
+    rich_text::{span_text_style, RichText, SpanStyle},
+    FontFamilyKind, FontSize, FontStack,
+};
+
+const BULLET: &str = "• ";
+const CODE_COLOR: Color = Palette::PINK_600;
+
+/// Formatting applied to a piece of the source.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct MarkdownStyle {
+    heading: Option<usize>,
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
+
+impl MarkdownStyle {
+    /// Combines the formatting with a span's own style, the span's explicit choices win.
+    fn apply(&self, style: &SpanStyle) -> SpanStyle {
+        let heading_size = self.heading.map(|level| match level {
+            1 => FontSize::Large,
+            _ => FontSize::Medium,
+        });
+
+        SpanStyle {
+            bold: style.bold || self.bold || self.heading.is_some_and(|level| level <= 2),
+            italic: style.italic || self.italic,
+            size: style.size.or(heading_size),
+            color: style.color.or(self.code.then_some(CODE_COLOR)),
+        }
+    }
+
+    fn font(&self, font: &FontDocument) -> FontDocument {
+        FontDocument {
+            family: if self.heading.is_some() {
+                FontFamilyKind::Title
+            } else {
+                font.family
+            },
+            ..*font
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PieceText {
+    /// A byte range of the source, shown as is.
+    Source(Range<usize>),
+    /// Text shown in place of markup, like list bullets.
+    Literal(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Piece {
+    text: PieceText,
+    style: MarkdownStyle,
+}
+
+/// The formatted sections of markdown source, keeping the styles of its spans.
+/// Only the display is formatted, the source stays as typed so it can be edited and saved.
+pub(crate) fn sections(
+    rich_text: &RichText,
+    font_stack: &FontStack,
+    text_color: Color,
+) -> Vec<TextSection> {
+    let source = rich_text.text();
+    let mut sections = Vec::new();
+
+    for piece in parse(&source) {
+        let font = piece.style.font(&rich_text.font);
+        match piece.text {
+            PieceText::Source(range) => {
+                for (range, style) in rich_text.spans_in(range) {
+                    sections.push(TextSection::new(
+                        &source[range],
+                        span_text_style(&font, &piece.style.apply(style), font_stack, text_color),
+                    ));
+                }
+            }
+            PieceText::Literal(text) => sections.push(TextSection::new(
+                text,
+                span_text_style(
+                    &font,
+                    &piece.style.apply(&SpanStyle::default()),
+                    font_stack,
+                    text_color,
+                ),
+            )),
+        }
+    }
+
+    if sections.is_empty() {
+        // An empty section still gives the text a line height
+        sections.push(TextSection::new(
+            "",
+            span_text_style(
+                &rich_text.font,
+                &SpanStyle::default(),
+                font_stack,
+                text_color,
+            ),
+        ));
+    }
+
+    sections
+}
+
+fn parse(source: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+
+    for line in source.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        parse_line(source, offset..offset + content.len(), &mut pieces);
+        if content.len() < line.len() {
+            push_source(
+                &mut pieces,
+                offset + content.len()..offset + line.len(),
+                MarkdownStyle::default(),
+            );
+        }
+        offset += line.len();
+    }
+
+    pieces
+}
+
+/// Handles the block markup at the start of a line, then its inline markup.
+fn parse_line(source: &str, range: Range<usize>, pieces: &mut Vec<Piece>) {
+    let line = &source[range.clone()];
+    let trimmed = line.trim_start();
+    let indent = range.start..range.start + line.len() - trimmed.len();
+    let bytes = trimmed.as_bytes();
+
+    let heading = trimmed.bytes().take_while(|byte| *byte == b'#').count();
+    if (1..=6).contains(&heading) && bytes.get(heading) == Some(&b' ') {
+        let style = MarkdownStyle {
+            heading: Some(heading),
+            ..Default::default()
+        };
+        parse_inline(source, indent.end + heading + 1..range.end, style, pieces);
+        return;
+    }
+
+    let is_list_marker = matches!(bytes, [b'-' | b'*' | b'+', b' ', ..]);
+    if is_list_marker
+        && matches!(
+            bytes.get(2..6),
+            Some([b'[', b' ' | b'x' | b'X', b']', b' '])
+        )
+    {
+        let checked = bytes[3] != b' ';
+        push_source(pieces, indent.clone(), MarkdownStyle::default());
+        // The box stays as typed, the marker before it is hidden
+        push_source(
+            pieces,
+            indent.end + 2..indent.end + 6,
+            MarkdownStyle {
+                bold: true,
+                ..Default::default()
+            },
+        );
+        let style = MarkdownStyle {
+            italic: checked,
+            ..Default::default()
+        };
+        parse_inline(source, indent.end + 6..range.end, style, pieces);
+        return;
+    }
+
+    if is_list_marker {
+        push_source(pieces, indent.clone(), MarkdownStyle::default());
+        pieces.push(Piece {
+            text: PieceText::Literal(BULLET),
+            style: MarkdownStyle::default(),
+        });
+        parse_inline(
+            source,
+            indent.end + 2..range.end,
+            MarkdownStyle::default(),
+            pieces,
+        );
+        return;
+    }
+
+    let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 && matches!(bytes.get(digits..digits + 2), Some([b'.' | b')', b' '])) {
+        let marker_end = indent.end + digits + 2;
+        push_source(pieces, indent.clone(), MarkdownStyle::default());
+        push_source(
+            pieces,
+            indent.end..marker_end,
+            MarkdownStyle {
+                bold: true,
+                ..Default::default()
+            },
+        );
+        parse_inline(
+            source,
+            marker_end..range.end,
+            MarkdownStyle::default(),
+            pieces,
+        );
+        return;
+    }
+
+    parse_inline(source, range, MarkdownStyle::default(), pieces);
+}
+
+/// Handles `**bold**`, `*italic*` (or with underscores) and `` `code` `` within a line.
+/// Markers without a closing one later in the line are shown as typed.
+fn parse_inline(source: &str, range: Range<usize>, base: MarkdownStyle, pieces: &mut Vec<Piece>) {
+    let text = &source[range.clone()];
+    let bytes = text.as_bytes();
+    let mut style = base;
+    let mut run_start = 0;
+    let mut index = 0;
+
+    // Markers are ASCII, so every index they are found at is a char boundary
+    while index < bytes.len() {
+        match bytes[index] {
+            b'`' => {
+                if let Some(length) = text[index + 1..].find('`') {
+                    push_source(pieces, range.start + run_start..range.start + index, style);
+                    push_source(
+                        pieces,
+                        range.start + index + 1..range.start + index + 1 + length,
+                        MarkdownStyle {
+                            code: true,
+                            ..style
+                        },
+                    );
+                    index += length + 2;
+                    run_start = index;
+                    continue;
+                }
+            }
+            marker @ (b'*' | b'_') => {
+                let double = bytes.get(index + 1) == Some(&marker);
+                let width = if double { 2 } else { 1 };
+                let delimiter = &text[index..index + width];
+                let is_open = if double { style.bold } else { style.italic };
+                // Underscores inside words, like in snake_case, aren't markup
+                let starts_word =
+                    marker == b'*' || index == 0 || !bytes[index - 1].is_ascii_alphanumeric();
+
+                if is_open || (starts_word && text[index + width..].contains(delimiter)) {
+                    push_source(pieces, range.start + run_start..range.start + index, style);
+                    if double {
+                        style.bold = !style.bold;
+                    } else {
+                        style.italic = !style.italic;
+                    }
+                    index += width;
+                    run_start = index;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    push_source(pieces, range.start + run_start..range.end, style);
+}
+
+fn push_source(pieces: &mut Vec<Piece>, range: Range<usize>, style: MarkdownStyle) {
+    if !range.is_empty() {
+        pieces.push(Piece {
+            text: PieceText::Source(range),
+            style,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: MarkdownStyle = MarkdownStyle {
+        heading: None,
+        bold: false,
+        italic: false,
+        code: false,
+    };
+    const BOLD: MarkdownStyle = MarkdownStyle {
+        bold: true,
+        ..PLAIN
+    };
+    const ITALIC: MarkdownStyle = MarkdownStyle {
+        italic: true,
+        ..PLAIN
+    };
+    const CODE: MarkdownStyle = MarkdownStyle {
+        code: true,
+        ..PLAIN
+    };
+
+    fn heading(level: usize) -> MarkdownStyle {
+        MarkdownStyle {
+            heading: Some(level),
+            ..PLAIN
+        }
+    }
+
+    /// The text of each piece, along with its style.
+    fn parsed(source: &str) -> Vec<(&str, MarkdownStyle)> {
+        parse(source)
+            .into_iter()
+            .map(|piece| match piece.text {
+                PieceText::Source(range) => (&source[range], piece.style),
+                PieceText::Literal(text) => (text, piece.style),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn headings_are_styled_by_level() {
+        assert_eq!(
+            parsed("# Title\n## Section\n### Note"),
+            vec![
+                ("Title", heading(1)),
+                ("\n", PLAIN),
+                ("Section", heading(2)),
+                ("\n", PLAIN),
+                ("Note", heading(3)),
+            ]
+        );
+        assert_eq!(parsed("#hashtag"), vec![("#hashtag", PLAIN)]);
+        assert_eq!(parsed("####### Seven"), vec![("####### Seven", PLAIN)]);
+    }
+
+    #[test]
+    fn bullet_markers_are_shown_as_bullets() {
+        assert_eq!(
+            parsed("- one\n* two\n  + nested"),
+            vec![
+                (BULLET, PLAIN),
+                ("one", PLAIN),
+                ("\n", PLAIN),
+                (BULLET, PLAIN),
+                ("two", PLAIN),
+                ("\n", PLAIN),
+                ("  ", PLAIN),
+                (BULLET, PLAIN),
+                ("nested", PLAIN),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbered_list_markers_are_bold() {
+        assert_eq!(
+            parsed("1. first\n10) tenth"),
+            vec![
+                ("1. ", BOLD),
+                ("first", PLAIN),
+                ("\n", PLAIN),
+                ("10) ", BOLD),
+                ("tenth", PLAIN),
+            ]
+        );
+        assert_eq!(parsed("1.5 apples"), vec![("1.5 apples", PLAIN)]);
+    }
+
+    #[test]
+    fn checked_items_are_italic() {
+        assert_eq!(
+            parsed("- [ ] todo\n- [x] done\n* [X] also done"),
+            vec![
+                ("[ ] ", BOLD),
+                ("todo", PLAIN),
+                ("\n", PLAIN),
+                ("[x] ", BOLD),
+                ("done", ITALIC),
+                ("\n", PLAIN),
+                ("[X] ", BOLD),
+                ("also done", ITALIC),
+            ]
+        );
+    }
+
+    #[test]
+    fn bold_and_italic_nest() {
+        assert_eq!(
+            parsed("**bold *both* bold**"),
+            vec![
+                ("bold ", BOLD),
+                (
+                    "both",
+                    MarkdownStyle {
+                        bold: true,
+                        italic: true,
+                        ..PLAIN
+                    }
+                ),
+                (" bold", BOLD),
+            ]
+        );
+        assert_eq!(
+            parsed("_italic_ and __bold__"),
+            vec![("italic", ITALIC), (" and ", PLAIN), ("bold", BOLD)]
+        );
+    }
+
+    #[test]
+    fn inline_code_is_not_formatted_further() {
+        assert_eq!(
+            parsed("run `cargo test` now"),
+            vec![("run ", PLAIN), ("cargo test", CODE), (" now", PLAIN)]
+        );
+        assert_eq!(parsed("`*not italic*`"), vec![("*not italic*", CODE)]);
+    }
+
+    #[test]
+    fn underscores_in_words_are_not_markup() {
+        assert_eq!(
+            parsed("use snake_case_names here"),
+            vec![("use snake_case_names here", PLAIN)]
+        );
+        assert_eq!(
+            parsed("snake_case and _this_"),
+            vec![("snake_case and ", PLAIN), ("this", ITALIC)]
+        );
+    }
+
+    #[test]
+    fn unclosed_markers_are_shown_as_typed() {
+        for source in ["2 * 3 = 6", "**not bold", "_not italic", "`not code"] {
+            assert_eq!(parsed(source), vec![(source, PLAIN)]);
+        }
+    }
+}
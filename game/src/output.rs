@@ -0,0 +1,187 @@
+use bevy::{asset::LoadState, ecs::query::QueryItem, utils::HashSet};
+use bevy_pancam::PanCam;
+
+use crate::{
+    events::{ItemInfo, OutputEvent, TxOutputEvent},
+    item::{item_type, update_item_index, Item, ItemId, ItemIndex, ItemTypeQuery},
+    post_it::ImageSprite,
+    prelude::*,
+    select::{components::Selected, SelectionMoved},
+};
+
+/// Reports what happens on the board to the web app.
+pub struct OutputPlugin;
+
+impl Plugin for OutputPlugin {
+    fn build(&self, app: &mut App) {
+        // Runs after the frame's commands are applied, so spawned, despawned and newly
+        // selected items are all visible
+        app.add_systems(
+            PostUpdate,
+            (
+                // The index still knows the ids of items despawned this frame, and the items
+                // respawned in their place by an undo, redo or load
+                send_items_added.before(update_item_index),
+                send_items_removed.before(update_item_index),
+                send_items_moved,
+                send_selection_changed,
+                send_camera_changed,
+                send_asset_load_failed,
+            ),
+        );
+    }
+}
+
+/// Marks image sprites whose failure has already been reported.
+#[derive(Component)]
+struct LoadFailed;
+
+type ItemInfoQuery = (&'static ItemId, &'static Transform, ItemTypeQuery);
+
+fn item_info((id, transform, types): QueryItem<ItemInfoQuery>) -> Option<ItemInfo> {
+    Some(ItemInfo {
+        id: *id,
+        item_type: item_type(types)?,
+        position: transform.translation.xy(),
+    })
+}
+
+/// Items respawned with the id of an item they replaced are reported as moved, they may have
+/// been put back somewhere else.
+fn send_items_added(
+    tx_output_event: Res<TxOutputEvent>,
+    item_index: Res<ItemIndex>,
+    added_query: Query<(Entity, ItemInfoQuery), Added<Item>>,
+) {
+    let (respawned, added): (Vec<_>, Vec<_>) =
+        added_query.iter().partition(|(entity, (id, ..))| {
+            item_index
+                .entity(**id)
+                .is_some_and(|indexed| indexed != *entity)
+        });
+    let info = |items: Vec<_>| {
+        items
+            .into_iter()
+            .filter_map(|(_, item)| item_info(item))
+            .collect::<Vec<_>>()
+    };
+    let (respawned, added) = (info(respawned), info(added));
+
+    if !added.is_empty() {
+        tx_output_event.emit(OutputEvent::ItemsAdded(added));
+    }
+    if !respawned.is_empty() {
+        tx_output_event.emit(OutputEvent::ItemsMoved(respawned));
+    }
+}
+
+fn send_items_removed(
+    tx_output_event: Res<TxOutputEvent>,
+    item_index: Res<ItemIndex>,
+    mut removed: RemovedComponents<Item>,
+    item_query: Query<&ItemId, With<Item>>,
+) {
+    // Items respawned in the same frame weren't removed
+    let remaining = item_query.iter().collect::<HashSet<_>>();
+    let ids = removed
+        .read()
+        .filter_map(|entity| item_index.id(entity))
+        .filter(|id| !remaining.contains(id))
+        .collect::<Vec<_>>();
+
+    if !ids.is_empty() {
+        tx_output_event.emit(OutputEvent::ItemsRemoved(ids));
+    }
+}
+
+fn send_items_moved(
+    tx_output_event: Res<TxOutputEvent>,
+    mut selection_moved_reader: EventReader<SelectionMoved>,
+    item_query: Query<ItemInfoQuery, With<Item>>,
+) {
+    for event in selection_moved_reader.read() {
+        let items = item_query
+            .iter_many(&event.entities)
+            .filter_map(item_info)
+            .collect::<Vec<_>>();
+
+        if !items.is_empty() {
+            tx_output_event.emit(OutputEvent::ItemsMoved(items));
+        }
+    }
+}
+
+fn send_selection_changed(
+    tx_output_event: Res<TxOutputEvent>,
+    newly_selected_query: Query<(), Added<Selected>>,
+    mut removed: RemovedComponents<Selected>,
+    selected_query: Query<ItemInfoQuery, With<Selected>>,
+) {
+    // Drain the removals either way, so they don't count towards the next frame
+    let deselected = removed.read().count() > 0;
+    if newly_selected_query.is_empty() && !deselected {
+        return;
+    }
+
+    let items = selected_query.iter().filter_map(item_info).collect();
+    tx_output_event.emit(OutputEvent::SelectionChanged(items));
+}
+
+type CameraChangedFilter = (
+    With<PanCam>,
+    Or<(Changed<Transform>, Changed<OrthographicProjection>)>,
+);
+
+fn send_camera_changed(
+    tx_output_event: Res<TxOutputEvent>,
+    camera_query: Query<(&Transform, &OrthographicProjection), CameraChangedFilter>,
+    mut last_camera: Local<Option<(Vec2, f32)>>,
+) {
+    let Ok((transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    // The pan camera touches its components on most frames, only report real changes
+    let camera = (transform.translation.xy(), projection.scale);
+    if *last_camera == Some(camera) {
+        return;
+    }
+    *last_camera = Some(camera);
+
+    tx_output_event.emit(OutputEvent::CameraChanged {
+        position: camera.0,
+        scale: camera.1,
+    });
+}
+
+type ImageSpriteQuery = (Entity, &'static Parent, &'static Handle<Image>);
+
+fn send_asset_load_failed(
+    mut commands: Commands,
+    tx_output_event: Res<TxOutputEvent>,
+    asset_server: Res<AssetServer>,
+    image_sprite_query: Query<ImageSpriteQuery, (With<ImageSprite>, Without<LoadFailed>)>,
+    item_query: Query<&ItemId>,
+) {
+    for (entity, parent, image) in &image_sprite_query {
+        if !matches!(
+            asset_server.get_load_state(image.id()),
+            Some(LoadState::Failed)
+        ) {
+            continue;
+        }
+
+        let Ok(id) = item_query.get(parent.get()) else {
+            continue;
+        };
+
+        commands.entity(entity).insert(LoadFailed);
+        tx_output_event.emit(OutputEvent::AssetLoadFailed {
+            item: *id,
+            source: image
+                .path()
+                .map(|path| path.to_string())
+                .unwrap_or_default(),
+        });
+    }
+}
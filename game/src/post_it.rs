@@ -1,12 +1,17 @@
 use bevy::text::{BreakLineOn, Text2dBounds};
+use bevy_pancam::PanCam;
 
 use crate::{
+    document::FontDocument,
     events::AddItemEvent,
-    item::ItemBundle,
+    image_store::ImageStore,
+    item::{item_aabb, ItemBundle, ItemOutlineQuery, ItemSize, SpawnItemEvent},
     prelude::*,
+    rich_text::RichText,
     select::components::Selected,
+    text_edit::TextEditor,
     theme::{Theme, ThemeDidChange},
-    FontStack, ImageCache,
+    FontFamilyKind, FontStack, FontVariant, ImageCache,
 };
 
 const POST_IT_SIZE: Vec2 = Vec2::new(400., 420.);
@@ -22,10 +27,13 @@ impl Plugin for PostItPlugin {
             Update,
             (
                 add_post_it,
+                add_image,
                 select_post_it,
                 remove_select.after(select_post_it),
                 post_it_theme_change,
                 image_inserted,
+                resize_post_its,
+                resize_images,
             ),
         );
     }
@@ -48,23 +56,70 @@ pub struct PostItText;
 
 fn add_post_it(
     mut commands: Commands,
-    mut events: EventReader<AddItemEvent>,
+    mut events: EventReader<SpawnItemEvent>,
     theme: Res<Theme>,
     font_stack: Res<FontStack>,
 ) {
     for event in events.read() {
-        if let AddItemEvent::PostIt(text) = event {
+        if let AddItemEvent::PostIt(text) = &event.item {
             spawn_post_it(
                 &mut commands,
                 &theme,
                 &font_stack,
-                Vec3::new(0., 0., 0.0),
-                text.as_str(),
+                event.position.unwrap_or_default().extend(0.),
+                RichText::plain(text.as_str(), post_it_font(&font_stack)),
             );
         }
     }
 }
 
+fn add_image(
+    mut commands: Commands,
+    mut events: EventReader<SpawnItemEvent>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    image_store: Res<ImageStore>,
+    mut image_cache: ResMut<ImageCache>,
+    camera_query: Query<&Transform, With<PanCam>>,
+) {
+    for event in events.read() {
+        let AddItemEvent::Image(source) = &event.item else {
+            continue;
+        };
+
+        // Data URIs are stored so the image can be saved with the board. Other URLs are fetched
+        // into the store by the web app, the asset server would look for them in the assets.
+        let path = if source.starts_with("data:") {
+            let Some(key) = image_store.insert_data_uri(source) else {
+                warn!("Unsupported image data URI");
+                continue;
+            };
+            ImageStore::asset_path(&key)
+        } else if ImageStore::key_from_asset_path(source).is_some() {
+            source.clone()
+        } else {
+            warn!("Images can only be added from data URIs or the image store, not {source}");
+            continue;
+        };
+
+        // Place new images in the middle of the viewport
+        let position = event.position.unwrap_or_else(|| {
+            camera_query
+                .get_single()
+                .map(|transform| transform.translation.xy())
+                .unwrap_or_default()
+        });
+
+        spawn_image(
+            &mut commands,
+            &theme,
+            position.extend(0.),
+            asset_server.load(path),
+            &mut image_cache,
+        );
+    }
+}
+
 fn select_post_it(
     mut newly_selected_query: Query<&mut Stroke, (Added<Selected>, With<PostIt>)>,
     theme: Res<Theme>,
@@ -94,7 +149,7 @@ pub(crate) fn spawn_image(
     position: Vec3,
     image: Handle<Image>,
     image_cache: &mut ResMut<ImageCache>,
-) {
+) -> Entity {
     commands
         .spawn((
             ItemBundle {
@@ -147,7 +202,17 @@ pub(crate) fn spawn_image(
                 .id();
 
             image_cache.images.insert(image.clone(), id);
-        });
+        })
+        .id()
+}
+
+/// The font of post-it text without any styling.
+pub(crate) fn post_it_font(font_stack: &FontStack) -> FontDocument {
+    FontDocument {
+        family: FontFamilyKind::Body,
+        variant: FontVariant::Regular,
+        size: font_stack.size.medium,
+    }
 }
 
 pub(crate) fn spawn_post_it(
@@ -155,14 +220,9 @@ pub(crate) fn spawn_post_it(
     theme: &Theme,
     font_stack: &FontStack,
     position: Vec3,
-    text: &str,
-) {
-    let text_style = TextStyle {
-        font: font_stack.body.regular().clone(),
-        font_size: font_stack.size.medium,
-        color: theme.default_text_color,
-        ..Default::default()
-    };
+    mut rich_text: RichText,
+) -> Entity {
+    rich_text.markdown = true;
 
     commands
         .spawn((
@@ -180,6 +240,7 @@ pub(crate) fn spawn_post_it(
                 ..Default::default()
             },
             PostIt,
+            ItemSize(POST_IT_SIZE),
             Name::new("Post-it Note"),
         ))
         .with_children(|builder| {
@@ -206,7 +267,7 @@ pub(crate) fn spawn_post_it(
                         size: POST_IT_SIZE * 0.8,
                     },
                     text: Text {
-                        sections: vec![TextSection::new(text, text_style.clone())],
+                        sections: rich_text.sections(font_stack, theme.default_text_color),
                         alignment: TextAlignment::Left,
                         linebreak_behavior: BreakLineOn::WordBoundary,
                     },
@@ -214,16 +275,51 @@ pub(crate) fn spawn_post_it(
                     transform: Transform::from_translation(Vec3::new(0., 0., 0.1)),
                     ..default()
                 },
+                rich_text,
                 PostItText,
             ));
-        });
+        })
+        .id()
 }
 
+/// The largest size that fits the image into `MAX_IMAGE_SIZE` without distorting it.
+fn fit_image_size(image: &Image) -> Vec2 {
+    let width = image.size().x as f32;
+    let height = image.size().y as f32;
+    let x_ratio = MAX_IMAGE_SIZE.x / width;
+    let y_ratio = MAX_IMAGE_SIZE.y / height;
+
+    let ratio = if x_ratio < y_ratio { x_ratio } else { y_ratio };
+
+    Vec2::new(width * ratio, height * ratio)
+}
+
+/// Sizes newly loaded images to fit `MAX_IMAGE_SIZE`, unless they were given a size already.
 fn image_inserted(
+    mut commands: Commands,
     image_assets: Res<Assets<Image>>,
-    mut image_sprite_query: Query<(&mut Sprite, &Handle<Image>), With<ImageSprite>>,
+    image_sprite_query: Query<(&Handle<Image>, &Parent, Ref<ImageSprite>)>,
+    sized_query: Query<(), With<ItemSize>>,
     mut ev_asset: EventReader<AssetEvent<Image>>,
 ) {
+    let mut fit = |image: &Image, parent: &Parent| {
+        if !sized_query.contains(parent.get()) {
+            commands
+                .entity(parent.get())
+                .insert(ItemSize(fit_image_size(image)));
+        }
+    };
+
+    // Images that were already loaded, e.g. when the same image is added twice
+    for (handle, parent, image_sprite) in &image_sprite_query {
+        if !image_sprite.is_added() {
+            continue;
+        }
+        if let Some(image) = image_assets.get(handle) {
+            fit(image, parent);
+        }
+    }
+
     for event in ev_asset.read() {
         match event {
             // AssetEvent::Added { id } => info!("Image Added: {:?}", id),
@@ -231,17 +327,9 @@ fn image_inserted(
             // AssetEvent::Removed { id } => info!("Image Removed: {:?}", id),
             AssetEvent::LoadedWithDependencies { id } => {
                 if let Some(image) = image_assets.get(*id) {
-                    for (mut sprite, handle) in &mut image_sprite_query.iter_mut() {
+                    for (handle, parent, _) in &image_sprite_query {
                         if handle.id() == *id {
-                            let width = image.size().x as f32;
-                            let height = image.size().y as f32;
-                            let x_ratio = MAX_IMAGE_SIZE.x / width;
-                            let y_ratio = MAX_IMAGE_SIZE.y / height;
-
-                            let ratio = if x_ratio < y_ratio { x_ratio } else { y_ratio };
-
-                            let new_size = Vec2::new(width * ratio, height * ratio);
-                            sprite.custom_size = Some(new_size)
+                            fit(image, parent);
                         }
                     }
                 } else {
@@ -252,17 +340,78 @@ fn image_inserted(
         }
     }
 }
+
+fn resize_post_its(
+    mut commands: Commands,
+    mut post_it_query: Query<ItemOutlineQuery, (With<PostIt>, Changed<ItemSize>)>,
+    mut shadow_query: Query<&mut Path, (With<PostItShadow>, Without<ItemSize>)>,
+    mut text_query: Query<&mut Text2dBounds, With<PostItText>>,
+) {
+    for (entity, size, children, mut path) in &mut post_it_query {
+        *path = GeometryBuilder::build_as(&shapes::Rectangle {
+            extents: size.0,
+            ..Default::default()
+        });
+        commands.entity(entity).insert(item_aabb(size.0));
+
+        for child in children {
+            if let Ok(mut shadow_path) = shadow_query.get_mut(*child) {
+                *shadow_path = GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: size.0,
+                    ..Default::default()
+                });
+            }
+            if let Ok(mut bounds) = text_query.get_mut(*child) {
+                // Wrap text in the rectangle
+                bounds.size = size.0 * 0.8;
+            }
+        }
+    }
+}
+
+fn resize_images(
+    mut commands: Commands,
+    mut image_query: Query<ItemOutlineQuery, (With<ImageItem>, Changed<ItemSize>)>,
+    mut shadow_query: Query<&mut Path, (With<PostItShadow>, Without<ItemSize>)>,
+    mut sprite_query: Query<&mut Sprite, With<ImageSprite>>,
+) {
+    for (entity, size, children, mut path) in &mut image_query {
+        *path = GeometryBuilder::build_as(&shapes::Rectangle {
+            extents: size.0,
+            ..Default::default()
+        });
+        commands.entity(entity).insert(item_aabb(size.0));
+
+        for child in children {
+            if let Ok(mut shadow_path) = shadow_query.get_mut(*child) {
+                *shadow_path = GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: size.0,
+                    ..Default::default()
+                });
+            }
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.custom_size = Some(size.0);
+            }
+        }
+    }
+}
+
+/// Editors render their own sections.
+type PostItTextFilter = (
+    With<PostItText>,
+    Without<PostIt>,
+    Without<PostItShadow>,
+    Without<TextEditor>,
+);
+
+type PostItShadowFilter = (With<PostItShadow>, Without<PostIt>, Without<PostItText>);
+
 fn post_it_theme_change(
     mut theme_event_reader: EventReader<ThemeDidChange>,
+    font_stack: Res<FontStack>,
     mut post_it_query: Query<(&mut Stroke, &mut Fill), With<PostIt>>,
-    mut post_it_text_query: Query<
-        &mut Text,
-        (With<PostItText>, Without<PostIt>, Without<PostItShadow>),
-    >,
-    mut post_it_shadow_query: Query<
-        &mut Fill,
-        (With<PostItShadow>, Without<PostIt>, Without<PostItText>),
-    >,
+    mut post_it_text_query: Query<(&RichText, &mut Text), PostItTextFilter>,
+    mut post_it_shadow_query: Query<&mut Fill, PostItShadowFilter>,
 ) {
     for event in theme_event_reader.read() {
         let theme = &event.theme;
@@ -273,8 +422,9 @@ fn post_it_theme_change(
             fill.color = theme.default_bg_color
         }
 
-        for mut text in &mut post_it_text_query {
-            text.sections[0].style.color = theme.default_text_color;
+        // Rebuilt from the spans, so explicitly colored spans keep their color
+        for (rich_text, mut text) in &mut post_it_text_query {
+            text.sections = rich_text.sections(&font_stack, theme.default_text_color);
         }
 
         for mut fill in &mut post_it_shadow_query {
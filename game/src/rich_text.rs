@@ -0,0 +1,372 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    document::FontDocument, markdown, prelude::*, text_edit::TextEditor, theme::Theme, FontSize,
+    FontStack, FontVariant,
+};
+
+pub struct RichTextPlugin;
+
+impl Plugin for RichTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, render_rich_text);
+    }
+}
+
+/// A run of text with a single style.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    #[serde(default)]
+    pub style: SpanStyle,
+}
+
+/// Styling on top of the item's font, the default leaves it as is.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub size: Option<FontSize>,
+    /// Spans without a color follow the theme's text color.
+    pub color: Option<Color>,
+}
+
+/// The styled spans of a post-it or canvas text, its `Text` sections are built from these.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub(crate) struct RichText {
+    pub spans: Vec<TextSpan>,
+    /// The font of spans without a size, bold or italic.
+    pub font: FontDocument,
+    /// Whether the text is markdown source, shown formatted unless it is being edited.
+    pub markdown: bool,
+}
+
+impl RichText {
+    pub fn new(spans: Vec<TextSpan>, font: FontDocument) -> Self {
+        let mut rich_text = Self {
+            spans,
+            font,
+            markdown: false,
+        };
+        rich_text.normalize();
+        rich_text
+    }
+
+    pub fn plain(text: impl Into<String>, font: FontDocument) -> Self {
+        Self::new(
+            vec![TextSpan {
+                text: text.into(),
+                style: SpanStyle::default(),
+            }],
+            font,
+        )
+    }
+
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Whether every span is unstyled, so the text alone describes it.
+    pub fn is_plain(&self) -> bool {
+        self.spans
+            .iter()
+            .all(|span| span.style == SpanStyle::default())
+    }
+
+    /// The style text typed at `position` gets, that of the char before it.
+    pub fn style_at(&self, position: usize) -> SpanStyle {
+        let mut offset = 0;
+        for span in &self.spans {
+            offset += span.text.len();
+            if position <= offset {
+                return span.style;
+            }
+        }
+
+        self.spans.last().map(|span| span.style).unwrap_or_default()
+    }
+
+    /// Replaces the text in `range`, which is a byte range of [`RichText::text`].
+    pub fn replace(&mut self, range: Range<usize>, value: &str, style: SpanStyle) {
+        let start = self.split_at(range.start);
+        let end = self.split_at(range.end);
+        self.spans.splice(
+            start..end,
+            [TextSpan {
+                text: value.to_string(),
+                style,
+            }],
+        );
+        self.normalize();
+    }
+
+    /// Whether `check` holds for every span overlapping `range`.
+    pub fn all(&self, range: Range<usize>, check: impl Fn(&SpanStyle) -> bool) -> bool {
+        let mut offset = 0;
+        self.spans.iter().all(|span| {
+            let span_range = offset..offset + span.text.len();
+            offset = span_range.end;
+            span_range.end <= range.start || span_range.start >= range.end || check(&span.style)
+        })
+    }
+
+    /// The parts of `range` covered by each span, with their style.
+    pub fn spans_in(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (Range<usize>, &SpanStyle)> + '_ {
+        let mut offset = 0;
+        self.spans.iter().filter_map(move |span| {
+            let span_range = offset..offset + span.text.len();
+            offset = span_range.end;
+            let start = span_range.start.max(range.start);
+            let end = span_range.end.min(range.end);
+            (start < end).then_some((start..end, &span.style))
+        })
+    }
+
+    pub fn update_style(&mut self, range: Range<usize>, update: impl Fn(&mut SpanStyle)) {
+        let start = self.split_at(range.start);
+        let end = self.split_at(range.end);
+        for span in &mut self.spans[start..end] {
+            update(&mut span.style);
+        }
+        self.normalize();
+    }
+
+    /// Splits the span containing `position` in two, and returns the index of the span
+    /// starting at `position`.
+    fn split_at(&mut self, position: usize) -> usize {
+        let mut offset = 0;
+        for index in 0..self.spans.len() {
+            let length = self.spans[index].text.len();
+            if position == offset {
+                return index;
+            }
+            if position < offset + length {
+                let span = &mut self.spans[index];
+                let text = span.text.split_off(position - offset);
+                let style = span.style;
+                self.spans.insert(index + 1, TextSpan { text, style });
+                return index + 1;
+            }
+            offset += length;
+        }
+
+        self.spans.len()
+    }
+
+    /// Merges neighbouring spans with the same style and drops empty ones.
+    fn normalize(&mut self) {
+        let mut spans: Vec<TextSpan> = Vec::with_capacity(self.spans.len());
+        for span in self.spans.drain(..) {
+            match spans.last_mut() {
+                _ if span.text.is_empty() => {}
+                Some(last) if last.style == span.style => last.text.push_str(&span.text),
+                _ => spans.push(span),
+            }
+        }
+        self.spans = spans;
+    }
+
+    /// The style of a section showing text styled with `style`.
+    pub fn text_style(
+        &self,
+        style: &SpanStyle,
+        font_stack: &FontStack,
+        text_color: Color,
+    ) -> TextStyle {
+        span_text_style(&self.font, style, font_stack, text_color)
+    }
+
+    pub fn sections(&self, font_stack: &FontStack, text_color: Color) -> Vec<TextSection> {
+        if self.markdown {
+            return markdown::sections(self, font_stack, text_color);
+        }
+
+        if self.spans.is_empty() {
+            // An empty section still gives the text a line height
+            return vec![TextSection::new(
+                "",
+                self.text_style(&SpanStyle::default(), font_stack, text_color),
+            )];
+        }
+
+        self.spans
+            .iter()
+            .map(|span| {
+                TextSection::new(
+                    span.text.clone(),
+                    self.text_style(&span.style, font_stack, text_color),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The style of a section showing text styled with `style` on top of `font`.
+pub(crate) fn span_text_style(
+    font: &FontDocument,
+    style: &SpanStyle,
+    font_stack: &FontStack,
+    text_color: Color,
+) -> TextStyle {
+    // There are no bold italic faces, bold wins
+    let variant = if style.bold {
+        FontVariant::Bold
+    } else if style.italic {
+        FontVariant::Italic
+    } else {
+        font.variant
+    };
+
+    TextStyle {
+        font: font_stack.font(font.family, variant),
+        font_size: style
+            .size
+            .map_or(font.size, |size| font_stack.size.get(size)),
+        color: style.color.unwrap_or(text_color),
+    }
+}
+
+/// Editors render their own sections.
+type RenderedTextFilter = (Changed<RichText>, Without<TextEditor>);
+
+fn render_rich_text(
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    mut text_query: Query<(&RichText, &mut Text), RenderedTextFilter>,
+) {
+    for (rich_text, mut text) in &mut text_query {
+        text.sections = rich_text.sections(&font_stack, theme.default_text_color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FontFamilyKind;
+
+    fn bold() -> SpanStyle {
+        SpanStyle {
+            bold: true,
+            ..Default::default()
+        }
+    }
+
+    fn italic() -> SpanStyle {
+        SpanStyle {
+            italic: true,
+            ..Default::default()
+        }
+    }
+
+    fn span(text: &str, style: SpanStyle) -> TextSpan {
+        TextSpan {
+            text: text.to_string(),
+            style,
+        }
+    }
+
+    fn rich_text(spans: Vec<TextSpan>) -> RichText {
+        RichText::new(
+            spans,
+            FontDocument {
+                family: FontFamilyKind::Body,
+                variant: FontVariant::Regular,
+                size: 16.,
+            },
+        )
+    }
+
+    #[test]
+    fn replacing_across_spans_keeps_the_ends_of_both() {
+        let mut text = rich_text(vec![
+            span("plain ", SpanStyle::default()),
+            span("bold ", bold()),
+            span("italic", italic()),
+        ]);
+
+        // From inside the first span to inside the last, dropping the one in between
+        text.replace(3.."plain bold it".len(), "ñ", SpanStyle::default());
+        assert_eq!(
+            text.spans,
+            [span("plañ", SpanStyle::default()), span("alic", italic())]
+        );
+        assert_eq!(text.text(), "plañalic");
+
+        // A differently styled replacement splits the span it lands in
+        text.replace(1..2, "L", bold());
+        assert_eq!(
+            text.spans,
+            [
+                span("p", SpanStyle::default()),
+                span("L", bold()),
+                span("añ", SpanStyle::default()),
+                span("alic", italic()),
+            ]
+        );
+    }
+
+    #[test]
+    fn styling_part_of_a_span_splits_it() {
+        let mut text = rich_text(vec![span("héllo world", SpanStyle::default())]);
+        let word = "hé".len().."héllo".len();
+
+        text.update_style(word.clone(), |style| style.bold = true);
+        assert_eq!(
+            text.spans,
+            [
+                span("hé", SpanStyle::default()),
+                span("llo", bold()),
+                span(" world", SpanStyle::default()),
+            ]
+        );
+        assert!(text.all(word.clone(), |style| style.bold));
+        assert!(!text.all(0..word.end, |style| style.bold));
+
+        // Toggling it back leaves a single span again
+        text.update_style(word, |style| style.bold = false);
+        assert_eq!(text.spans, [span("héllo world", SpanStyle::default())]);
+    }
+
+    #[test]
+    fn styles_at_span_edges_are_those_of_the_char_before() {
+        let text = rich_text(vec![span("ab", bold()), span("cd", italic())]);
+
+        // Nothing is before the start, it takes the first span's
+        assert_eq!(text.style_at(0), bold());
+        assert_eq!(text.style_at(1), bold());
+        assert_eq!(text.style_at(2), bold());
+        assert_eq!(text.style_at(3), italic());
+        assert_eq!(text.style_at(4), italic());
+        assert_eq!(text.style_at(10), italic());
+    }
+
+    #[test]
+    fn neighbouring_spans_merge_once_they_share_a_style() {
+        let mut text = rich_text(vec![
+            span("ab", bold()),
+            span("cd", SpanStyle::default()),
+            span("ef", bold()),
+        ]);
+
+        text.update_style(2..4, |style| style.bold = true);
+        assert_eq!(text.spans, [span("abcdef", bold())]);
+
+        text.update_style(0..6, |style| style.bold = false);
+        assert!(text.is_plain());
+        assert_eq!(text.spans.len(), 1);
+
+        // Empty spans are dropped, also when they were given
+        let text = rich_text(vec![
+            span("a", bold()),
+            span("", italic()),
+            span("b", bold()),
+        ]);
+        assert_eq!(text.spans, [span("ab", bold())]);
+    }
+}
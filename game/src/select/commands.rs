@@ -0,0 +1,214 @@
+use bevy::ecs::query::QueryItem;
+
+use crate::{
+    color_swatch::ColorSwatchColor,
+    drawing::PenStroke,
+    events::{ItemType, SelectionEvent},
+    item::{item_type, ItemTypeQuery},
+    keymap::{Action, Keymap},
+    prelude::*,
+    rich_text::RichText,
+    shape::ShapeItem,
+};
+
+use super::components::{Selectable, Selected};
+
+/// How far apart colors can be, in RGB, to count as similar.
+const SIMILAR_COLOR_DISTANCE: f32 = 0.1;
+
+pub(super) fn selection_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut selection_writer: EventWriter<SelectionEvent>,
+) {
+    let shortcuts = [
+        (Action::SelectAll, SelectionEvent::All),
+        (Action::SelectSimilarColor, SelectionEvent::SimilarColor),
+        (Action::InvertSelection, SelectionEvent::Invert),
+        (
+            Action::SelectPostIts,
+            SelectionEvent::OfType(ItemType::PostIt),
+        ),
+        (Action::SelectText, SelectionEvent::OfType(ItemType::Text)),
+        (
+            Action::SelectSwatches,
+            SelectionEvent::OfType(ItemType::Swatch),
+        ),
+        (
+            Action::SelectImages,
+            SelectionEvent::OfType(ItemType::Image),
+        ),
+    ];
+
+    for (action, event) in shortcuts {
+        if keymap.just_pressed(action, &keys) {
+            selection_writer.send(event);
+        }
+    }
+}
+
+type SelectableItemQuery = (
+    Entity,
+    &'static Transform,
+    Has<Selected>,
+    ItemTypeQuery,
+    ItemColorQuery,
+);
+
+/// Where the item types keep their colors: swatches and text in their children, shapes and
+/// pen strokes on the item.
+type ItemColorQuery = (
+    Option<&'static Children>,
+    Option<&'static ShapeItem>,
+    Option<&'static PenStroke>,
+);
+
+pub(super) fn apply_selection_events(
+    mut commands: Commands,
+    mut events: EventReader<SelectionEvent>,
+    selectable_query: Query<SelectableItemQuery, With<Selectable>>,
+    swatch_color_query: Query<&Fill, With<ColorSwatchColor>>,
+    rich_text_query: Query<&RichText>,
+) {
+    for event in events.read() {
+        let colors_of = |colors| item_colors(colors, &swatch_color_query, &rich_text_query);
+        let selected_colors = selectable_query
+            .iter()
+            .filter(|(_, _, is_selected, _, _)| *is_selected)
+            .flat_map(|(_, _, _, _, colors)| colors_of(colors))
+            .collect::<Vec<_>>();
+
+        for (entity, transform, is_selected, types, colors) in &selectable_query {
+            let selects = match event {
+                SelectionEvent::All => true,
+                SelectionEvent::OfType(selected_type) => item_type(types) == Some(*selected_type),
+                SelectionEvent::SimilarColor => colors_of(colors).into_iter().any(|color| {
+                    selected_colors
+                        .iter()
+                        .any(|selected| color_distance(color, *selected) <= SIMILAR_COLOR_DISTANCE)
+                }),
+                SelectionEvent::Invert => !is_selected,
+            };
+
+            if selects && !is_selected {
+                commands
+                    .entity(entity)
+                    .insert(Selected::new(transform.translation.xy()));
+            } else if !selects && is_selected {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+}
+
+/// The colors an item is drawn in, like a swatch's color, colored text, a shape's fill and
+/// outline or a pen stroke's color.
+fn item_colors(
+    (children, shape, stroke): QueryItem<ItemColorQuery>,
+    swatch_color_query: &Query<&Fill, With<ColorSwatchColor>>,
+    rich_text_query: &Query<&RichText>,
+) -> Vec<Color> {
+    let child_color = children.into_iter().flatten().find_map(|child| {
+        swatch_color_query
+            .get(*child)
+            .map(|fill| fill.color)
+            .ok()
+            .or_else(|| {
+                rich_text_query
+                    .get(*child)
+                    .ok()?
+                    .spans
+                    .iter()
+                    .find_map(|span| span.style.color)
+            })
+    });
+
+    child_color
+        .into_iter()
+        .chain(
+            shape
+                .into_iter()
+                .flat_map(|shape| [shape.fill, shape.stroke]),
+        )
+        .chain(stroke.map(|stroke| stroke.color))
+        // Shapes without a fill or outline aren't drawn in it
+        .filter(|color| color.a() > 0.)
+        .collect()
+}
+
+fn color_distance(a: Color, b: Color) -> f32 {
+    let [ar, ag, ab, _] = a.as_rgba_f32();
+    let [br, bg, bb, _] = b.as_rgba_f32();
+
+    Vec3::new(ar, ag, ab).distance(Vec3::new(br, bg, bb))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::events::ShapeKind;
+
+    fn shape(fill: Color, stroke: Color) -> ShapeItem {
+        ShapeItem {
+            kind: ShapeKind::Rectangle,
+            fill,
+            stroke,
+            stroke_width: 4.,
+            corner_radius: 0.,
+        }
+    }
+
+    fn pen_stroke(color: Color) -> PenStroke {
+        PenStroke {
+            points: vec![Vec2::ZERO, Vec2::ONE],
+            color,
+            width: 4.,
+            highlighter: false,
+        }
+    }
+
+    #[test]
+    fn similar_colors_match_shape_fills_outlines_and_pen_strokes() {
+        let mut world = World::new();
+        world.init_resource::<Events<SelectionEvent>>();
+        let item = || (Selectable, Transform::default());
+
+        let selected = world
+            .spawn((
+                item(),
+                shape(Palette::SKY_200, Color::NONE),
+                Selected::new(Vec2::ZERO),
+            ))
+            .id();
+        let same_fill = world
+            .spawn((item(), shape(Palette::SKY_200, Palette::GRAY_700)))
+            .id();
+        // Close enough, but not the same
+        let similar_outline = world
+            .spawn((item(), shape(Color::NONE, Palette::SKY_200.with_r(0.75))))
+            .id();
+        let similar_stroke = world.spawn((item(), pen_stroke(Palette::SKY_200))).id();
+        let other_shape = world
+            .spawn((item(), shape(Palette::PINK_600, Palette::GRAY_700)))
+            .id();
+        let other_stroke = world.spawn((item(), pen_stroke(Palette::PINK_600))).id();
+        // Unfilled shapes don't match each other through their missing fill
+        let unfilled = world
+            .spawn((item(), shape(Color::NONE, Palette::PINK_600)))
+            .id();
+
+        world.send_event(SelectionEvent::SimilarColor);
+        world.run_system_once(apply_selection_events);
+
+        let is_selected = |entity| world.get::<Selected>(entity).is_some();
+        assert!(is_selected(selected));
+        assert!(is_selected(same_fill));
+        assert!(is_selected(similar_outline));
+        assert!(is_selected(similar_stroke));
+        assert!(!is_selected(other_shape));
+        assert!(!is_selected(other_stroke));
+        assert!(!is_selected(unfilled));
+    }
+}
@@ -1,19 +1,54 @@
+use bevy::render::primitives::Aabb;
+
 use crate::prelude::*;
 
+use super::{
+    utils::{contained_in_polygon, contained_in_rect, intersects_polygon, intersects_rect},
+    SelectMode,
+};
+
+/// Lasso points closer than this to the previous one are skipped.
+const LASSO_POINT_SPACING: f32 = 4.0;
+
 #[derive(Component)]
 pub(crate) struct SelectionBox {
     pub start_position: Vec2,
     pub end_position: Vec2,
+    pub mode: SelectMode,
+    /// The items selected before the box was started, which Shift and Ctrl boxes build on.
+    pub initial_selection: Vec<Entity>,
+    /// The path traced with the lasso tool, selecting what it encloses instead of the box.
+    pub lasso: Option<Vec<Vec2>>,
 }
 
 impl SelectionBox {
     pub fn update(&mut self, position: Vec2) {
         self.end_position = position;
+
+        if let Some(points) = &mut self.lasso {
+            if points
+                .last()
+                .map_or(true, |last| last.distance(position) >= LASSO_POINT_SPACING)
+            {
+                points.push(position);
+            }
+        }
     }
 
     pub fn rect(&self) -> Rect {
         Rect::from_corners(self.start_position, self.end_position)
     }
+
+    /// Whether the box or lasso selects an item, either by touching it or, when `contained` is
+    /// set, only by surrounding it.
+    pub fn hits(&self, transform: &GlobalTransform, aabb: &Aabb, contained: bool) -> bool {
+        match (&self.lasso, contained) {
+            (Some(points), true) => contained_in_polygon(transform, aabb, points),
+            (Some(points), false) => intersects_polygon(transform, aabb, points),
+            (None, true) => contained_in_rect(transform, aabb, self.rect()),
+            (None, false) => intersects_rect(transform, aabb, self.rect()),
+        }
+    }
 }
 
 #[derive(Component)]
@@ -49,6 +84,10 @@ impl SelectedRect {
         self.rect.contains(position)
     }
 
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
     pub fn initial_rect(&self) -> Rect {
         self.initial_rect
     }
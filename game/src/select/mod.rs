@@ -1,15 +1,33 @@
 #![allow(clippy::type_complexity)]
 use crate::canvas::CursorCoords;
+use crate::drawing::not_drawing;
+use crate::events::{SelectItemsEvent, SelectionTool, SelectionToolEvent};
+use crate::item::{Item, ItemIndex};
+use crate::keymap::{Action, Keymap};
 use crate::prelude::*;
+use crate::text_edit::not_editing_text;
 use bevy::render::primitives::Aabb;
 
 use self::{
+    commands::{apply_selection_events, selection_shortcuts},
     components::{Selectable, Selected, SelectedRect, SelectionBox},
-    utils::{get_anchor, get_surrounding_rect},
+    nudge::nudge_selection,
+    resize::{
+        not_resizing, position_handles, resize_selection, spawn_handles, start_resize, Resizing,
+    },
+    rotate::{
+        not_rotating, position_rotate_handle, rotate_selection, spawn_rotate_handle, start_rotate,
+        Rotating,
+    },
+    utils::{contains_point, get_anchor, get_surrounding_rect, vector_to_parent_space},
 };
 
+mod commands;
 pub mod components;
-mod utils;
+mod nudge;
+mod resize;
+mod rotate;
+pub(crate) mod utils;
 
 const MAX_Z: f32 = 999.0;
 const SELECT_BOX_COLOR: Color = Palette::BLUE_400;
@@ -21,28 +39,123 @@ pub struct SelectPlugin;
 
 impl Plugin for SelectPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                create_selected_rect,
-                update_selected_rect,
-                select_entities,
-                move_selected_entities,
-                remove_selected_rect.after(move_selected_entities),
-                start_selection_box,
-                size_selection_box,
-                end_selection_box.after(size_selection_box),
-                clear_selected_on_insert,
-            ),
-        );
+        app.add_event::<SelectionMoved>()
+            .add_event::<SelectionTransformed>()
+            .init_resource::<Resizing>()
+            .init_resource::<Rotating>()
+            .init_resource::<SelectionTool>()
+            .add_systems(
+                Update,
+                (
+                    create_selected_rect,
+                    update_selected_rect,
+                    // Pressing a handle starts a resize or a rotation instead of a move or a new
+                    // selection
+                    start_resize
+                        .run_if(not_drawing)
+                        .before(select_entities)
+                        .before(move_selected_entities)
+                        .before(start_selection_box),
+                    resize_selection
+                        .after(start_resize)
+                        .before(move_selected_entities),
+                    start_rotate
+                        .run_if(not_drawing)
+                        .before(select_entities)
+                        .before(move_selected_entities)
+                        .before(start_selection_box),
+                    rotate_selection
+                        .after(start_rotate)
+                        .before(move_selected_entities),
+                    position_handles.after(update_selected_rect),
+                    position_rotate_handle.after(update_selected_rect),
+                    // Dragging draws instead while a drawing tool is picked
+                    select_entities
+                        .run_if(not_resizing.and_then(not_rotating).and_then(not_drawing)),
+                    move_selected_entities
+                        .run_if(not_resizing.and_then(not_rotating).and_then(not_drawing)),
+                    remove_selected_rect.after(move_selected_entities),
+                    start_selection_box
+                        .run_if(not_resizing.and_then(not_rotating).and_then(not_drawing)),
+                    size_selection_box,
+                    end_selection_box.after(size_selection_box),
+                    clear_selected_on_insert,
+                    set_selection_tool.before(start_selection_box),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    select_items,
+                    selection_tool_shortcuts.run_if(not_editing_text),
+                    selection_shortcuts.run_if(not_editing_text),
+                    apply_selection_events.after(selection_shortcuts),
+                    nudge_selection.run_if(
+                        not_editing_text
+                            .and_then(not_resizing)
+                            .and_then(not_rotating),
+                    ),
+                ),
+            );
+    }
+}
+
+/// How a click or a selection box changes the selection, from the modifiers held when it starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SelectMode {
+    /// Selects only the clicked or boxed items.
+    #[default]
+    Replace,
+    /// Shift adds the items to the selection.
+    Add,
+    /// Ctrl or Cmd selects unselected items and deselects selected ones.
+    Toggle,
+}
+
+impl SelectMode {
+    pub fn from_keys(keys: &Input<KeyCode>) -> Self {
+        if keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]) {
+            Self::Toggle
+        } else if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            Self::Add
+        } else {
+            Self::Replace
+        }
+    }
+
+    /// Whether an item ends up selected, given if it was selected before and if it was hit.
+    fn selects(&self, was_selected: bool, hit: bool) -> bool {
+        match self {
+            Self::Replace => hit,
+            Self::Add => was_selected || hit,
+            Self::Toggle => was_selected != hit,
+        }
     }
 }
 
+/// Sent when a drag of the selection is committed, with the items that actually moved.
+#[derive(Event)]
+pub(crate) struct SelectionMoved {
+    pub entities: Vec<Entity>,
+}
+
+/// Sent when a resize or rotation of the selection is committed.
+#[derive(Event)]
+pub(crate) struct SelectionTransformed;
+
 // Selection Box Systems
 fn start_selection_box(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    selection_tool: Res<SelectionTool>,
     selectable_query: Query<(&GlobalTransform, &Aabb), With<Selectable>>,
+    selected_query: Query<Entity, With<Selected>>,
     selected_rect_query: Query<&SelectedRect>,
     cursor_coords: Res<CursorCoords>,
 ) {
@@ -55,13 +168,19 @@ fn start_selection_box(
             }
         }
 
-        if selectable_query.iter().any(|(transform, aabb)| {
-            Rect::from_center_half_size(transform.translation().xy(), aabb.half_extents.xy())
-                .contains(coords)
-        }) {
+        if selectable_query
+            .iter()
+            .any(|(transform, aabb)| contains_point(transform, aabb, coords))
+        {
             return;
         }
 
+        let mode = SelectMode::from_keys(&keys);
+        let initial_selection = match mode {
+            SelectMode::Replace => Vec::new(),
+            SelectMode::Add | SelectMode::Toggle => selected_query.iter().collect(),
+        };
+
         commands.spawn((
             ShapeBundle {
                 path: GeometryBuilder::build_as(&shapes::Rectangle {
@@ -78,6 +197,9 @@ fn start_selection_box(
             SelectionBox {
                 start_position: coords,
                 end_position: coords,
+                mode,
+                initial_selection,
+                lasso: (*selection_tool == SelectionTool::Lasso).then(|| vec![coords]),
             },
             Name::new("Selection Box"),
         ));
@@ -92,13 +214,25 @@ fn size_selection_box(
     >,
 ) {
     if let Ok((mut selection_box, mut path)) = selection_box_query.get_single_mut() {
+        selection_box.update(cursor_coords.current);
+
+        if let Some(points) = &selection_box.lasso {
+            // The path is drawn relative to where the lasso started
+            *path = GeometryBuilder::build_as(&shapes::Polygon {
+                points: points
+                    .iter()
+                    .map(|point| *point - selection_box.start_position)
+                    .collect(),
+                closed: true,
+            });
+            return;
+        }
+
         let distance = cursor_coords.hold_distance();
         *path = GeometryBuilder::build_as(&shapes::Rectangle {
             extents: distance.abs(),
             origin: get_anchor(distance),
         });
-
-        selection_box.update(cursor_coords.current);
     }
 }
 
@@ -125,50 +259,67 @@ fn create_selected_rect(
     };
 
     if let Some(rect) = get_surrounding_rect(newly_selected_query.iter().collect::<Vec<_>>()) {
-        commands.spawn((
-            ShapeBundle {
-                path: GeometryBuilder::build_as(&shapes::Rectangle {
-                    extents: Vec2::new(rect.width(), rect.height()),
-                    origin: shapes::RectangleOrigin::Center,
-                }),
-                spatial: SpatialBundle::from_transform(Transform::from_xyz(
-                    rect.center().x,
-                    rect.center().y,
-                    MAX_Z,
-                )),
-                ..Default::default()
-            },
-            Stroke::new(SELECTED_RECT_COLOR, SELECTED_RECT_STROKE_WIDTH),
-            SelectedRect::new(rect),
-            Name::new("Selected Rect"),
-        ));
+        commands
+            .spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shapes::Rectangle {
+                        extents: Vec2::new(rect.width(), rect.height()),
+                        origin: shapes::RectangleOrigin::Center,
+                    }),
+                    spatial: SpatialBundle::from_transform(Transform::from_xyz(
+                        rect.center().x,
+                        rect.center().y,
+                        MAX_Z,
+                    )),
+                    ..Default::default()
+                },
+                Stroke::new(SELECTED_RECT_COLOR, SELECTED_RECT_STROKE_WIDTH),
+                SelectedRect::new(rect),
+                Name::new("Selected Rect"),
+            ))
+            .with_children(|builder| {
+                spawn_handles(builder, rect);
+                spawn_rotate_handle(builder, rect);
+            });
     }
 }
 
 fn update_selected_rect(
     new_selected_query: Query<Entity, Added<Selected>>,
+    // Text items grow while they are edited
+    resized_query: Query<Entity, (With<Selected>, Changed<Aabb>)>,
     selected_query: Query<(&GlobalTransform, &Aabb), With<Selected>>,
     mut selected_rect_query: Query<(&mut SelectedRect, &mut Transform, &mut Path)>,
     removed: RemovedComponents<Selected>,
 ) {
-    if new_selected_query.is_empty() && removed.is_empty() {
+    if new_selected_query.is_empty() && resized_query.is_empty() && removed.is_empty() {
         return;
     };
 
     if let Ok((mut selected_rect, mut transform, mut path)) = selected_rect_query.get_single_mut() {
         if let Some(rect) = get_surrounding_rect(selected_query.iter().collect::<Vec<_>>()) {
-            selected_rect.update(rect);
-            selected_rect.commit();
-
-            transform.translation = rect.center().extend(MAX_Z);
-            *path = GeometryBuilder::build_as(&shapes::Rectangle {
-                extents: Vec2::new(rect.width(), rect.height()),
-                origin: shapes::RectangleOrigin::Center,
-            });
+            fit_selected_rect(&mut selected_rect, &mut transform, &mut path, rect);
         }
     }
 }
 
+/// Moves and sizes the selected rect to surround `rect`.
+fn fit_selected_rect(
+    selected_rect: &mut SelectedRect,
+    transform: &mut Transform,
+    path: &mut Path,
+    rect: Rect,
+) {
+    selected_rect.update(rect);
+    selected_rect.commit();
+
+    transform.translation = rect.center().extend(MAX_Z);
+    *path = GeometryBuilder::build_as(&shapes::Rectangle {
+        extents: Vec2::new(rect.width(), rect.height()),
+        origin: shapes::RectangleOrigin::Center,
+    });
+}
+
 fn remove_selected_rect(
     mut commands: Commands,
     selected_query: Query<Entity, With<Selected>>,
@@ -176,17 +327,18 @@ fn remove_selected_rect(
 ) {
     if selected_query.is_empty() {
         if let Ok(entity) = selected_rect_query.get_single_mut() {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
     };
 }
 
 fn clear_selected_on_insert(
     mut commands: Commands,
-    newly_selectable_query: Query<Entity, Added<Selectable>>,
+    // Items spawned already selected, like pasted ones, replace the selection themselves
+    new_item_query: Query<Entity, (Added<Item>, With<Selectable>, Without<Selected>)>,
     mut selected_query: Query<Entity, With<Selected>>,
 ) {
-    if newly_selectable_query.is_empty() || selected_query.is_empty() {
+    if new_item_query.is_empty() || selected_query.is_empty() {
         return;
     };
 
@@ -199,26 +351,41 @@ fn move_selected_entities(
     mut commands: Commands,
     cursor_coords: Res<CursorCoords>,
     mouse_button_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
     mut selected_rect_query: Query<(&mut SelectedRect, &mut Transform), Without<Selected>>,
     selection_box_query: Query<&SelectionBox>,
-    mut selected_query: Query<(Entity, &mut Selected, &mut Transform)>,
+    mut selected_query: Query<(Entity, &mut Selected, &mut Transform, Option<&Parent>)>,
+    parent_query: Query<&GlobalTransform>,
+    mut selection_moved_writer: EventWriter<SelectionMoved>,
 ) {
     // Commit new positions on mouse release
     if mouse_button_input.just_released(MouseButton::Left) {
         if let Ok((mut selected_rect, _)) = selected_rect_query.get_single_mut() {
             selected_rect.commit();
 
-            for (_, mut selected, transform) in &mut selected_query {
+            let mut moved = Vec::new();
+            for (entity, mut selected, transform, _) in &mut selected_query {
+                if selected.start_position != transform.translation.xy() {
+                    moved.push(entity);
+                }
                 selected.start_position = transform.translation.xy();
             }
+
+            if !moved.is_empty() {
+                selection_moved_writer.send(SelectionMoved { entities: moved });
+            }
         }
 
         return;
     }
-    if mouse_button_input.just_pressed(MouseButton::Left) && !selected_query.is_empty() {
+    // Clicks with Shift or Ctrl build on the selection instead of clearing it
+    if mouse_button_input.just_pressed(MouseButton::Left)
+        && !selected_query.is_empty()
+        && SelectMode::from_keys(&keys) == SelectMode::Replace
+    {
         if let Ok((selected_rect, _)) = selected_rect_query.get_single() {
             if !selected_rect.initial_rect().contains(cursor_coords.current) {
-                for (entity, _, _) in &mut selected_query {
+                for (entity, _, _, _) in &mut selected_query {
                     commands.entity(entity).remove::<Selected>();
                 }
             }
@@ -235,8 +402,11 @@ fn move_selected_entities(
                     transform.translation = (start + distance).extend(transform.translation.z);
                     selected_rect.move_to(transform.translation.xy());
 
-                    for (_, selected, mut transform) in &mut selected_query {
-                        let distance = cursor_coords.hold_distance();
+                    for (_, selected, mut transform, parent) in &mut selected_query {
+                        // Members of groups move in the space of their group
+                        let parent = parent.and_then(|parent| parent_query.get(parent.get()).ok());
+                        let distance =
+                            vector_to_parent_space(parent, cursor_coords.hold_distance());
                         let start = selected.start_position;
 
                         transform.translation = (start + distance).extend(transform.translation.z);
@@ -247,60 +417,124 @@ fn move_selected_entities(
     }
 }
 
-fn select_entities(
+pub(crate) fn select_entities(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
     cursor_coords: ResMut<CursorCoords>,
-    mut selectable_query: Query<
-        (Entity, &GlobalTransform, &Aabb),
-        (With<Selectable>, Without<Selected>),
+    selectable_query: Query<
+        (Entity, &GlobalTransform, &Transform, &Aabb, Has<Selected>),
+        With<Selectable>,
     >,
     selection_box_query: Query<&SelectionBox, Without<Selectable>>,
 ) {
     if let Ok(selection_box) = selection_box_query.get_single() {
-        let selection_rect = selection_box.rect();
-
-        selectable_query.for_each(|(selectable_entity, transform, aabb)| {
-            let position = transform.translation().xy();
-            let selectable_rect = Rect::from_center_half_size(position, aabb.half_extents.xy());
-
-            if !selection_rect.intersect(selectable_rect).is_empty() {
-                commands
-                    .entity(selectable_entity)
-                    .insert(Selected::new(position));
-            } else {
-                commands.entity(selectable_entity).remove::<Selected>();
-            }
-        });
+        // Alt only selects items the box or lasso fully surrounds
+        let contained = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+
+        selectable_query.for_each(
+            |(selectable_entity, global_transform, transform, aabb, is_selected)| {
+                let was_selected = selection_box.initial_selection.contains(&selectable_entity);
+                let hit = selection_box.hits(global_transform, aabb, contained);
+
+                match (selection_box.mode.selects(was_selected, hit), is_selected) {
+                    (true, false) => {
+                        commands
+                            .entity(selectable_entity)
+                            .insert(Selected::new(transform.translation.xy()));
+                    }
+                    (false, true) => {
+                        commands.entity(selectable_entity).remove::<Selected>();
+                    }
+                    _ => {}
+                }
+            },
+        );
     }
 
     if mouse_button_input.just_pressed(MouseButton::Left) {
-        let mut topmost_entity: Option<(Entity, Vec3)> = None;
+        let mut topmost_entity: Option<(Entity, Vec3, Vec2, bool)> = None;
         // If this gets more complex, look into this package:
         // https://github.com/aevyrie/bevy_mod_picking/issues/7
-        for (entity, global_transform, aabb) in &selectable_query {
+        for (entity, global_transform, transform, aabb, is_selected) in &selectable_query {
             let translation = global_transform.translation();
             let is_cursor_over_selectable =
-                Rect::from_center_half_size(translation.xy(), aabb.half_extents.xy())
-                    .contains(cursor_coords.current);
+                contains_point(global_transform, aabb, cursor_coords.current);
+            // Items in groups start from their position in the group
+            let position = transform.translation.xy();
 
             if is_cursor_over_selectable {
-                if let Some((_, top_translation)) = topmost_entity {
+                if let Some((_, top_translation, _, _)) = topmost_entity {
                     if top_translation.z < translation.z {
-                        topmost_entity = Some((entity, translation));
+                        topmost_entity = Some((entity, translation, position, is_selected));
                     }
                 } else {
-                    topmost_entity = Some((entity, translation));
+                    topmost_entity = Some((entity, translation, position, is_selected));
                 }
             }
         }
 
-        if let Some((topmost_entity, translation)) = topmost_entity {
-            if let Ok((entity, _, _)) = selectable_query.get_mut(topmost_entity) {
-                commands
-                    .entity(entity)
-                    .insert(Selected::new(translation.xy()));
+        if let Some((entity, _, position, is_selected)) = topmost_entity {
+            let selects = SelectMode::from_keys(&keys).selects(is_selected, true);
+
+            if selects && !is_selected {
+                commands.entity(entity).insert(Selected::new(position));
+            } else if !selects && is_selected {
+                commands.entity(entity).remove::<Selected>();
             }
         }
     }
 }
+
+fn set_selection_tool(
+    mut events: EventReader<SelectionToolEvent>,
+    mut selection_tool: ResMut<SelectionTool>,
+) {
+    if let Some(event) = events.read().last() {
+        *selection_tool = event.tool;
+    }
+}
+
+fn selection_tool_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut selection_tool_writer: EventWriter<SelectionToolEvent>,
+    selection_tool: Res<SelectionTool>,
+) {
+    if keymap.just_pressed(Action::ToggleLasso, &keys) {
+        selection_tool_writer.send(SelectionToolEvent {
+            tool: match *selection_tool {
+                SelectionTool::Box => SelectionTool::Lasso,
+                SelectionTool::Lasso => SelectionTool::Box,
+            },
+        });
+    }
+}
+
+fn select_items(
+    mut commands: Commands,
+    mut events: EventReader<SelectItemsEvent>,
+    item_index: Res<ItemIndex>,
+    selectable_query: Query<&Transform, With<Selectable>>,
+    selected_query: Query<Entity, With<Selected>>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+
+    for entity in &selected_query {
+        commands.entity(entity).remove::<Selected>();
+    }
+
+    for id in &event.items {
+        let Some(entity) = item_index.entity(*id) else {
+            continue;
+        };
+
+        if let Ok(transform) = selectable_query.get(entity) {
+            commands
+                .entity(entity)
+                .insert(Selected::new(transform.translation.xy()));
+        }
+    }
+}
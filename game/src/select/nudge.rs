@@ -0,0 +1,59 @@
+use crate::{
+    keymap::{Action, Keymap},
+    prelude::*,
+};
+
+use super::{
+    components::{Selected, SelectedRect},
+    utils::vector_to_parent_space,
+    SelectionMoved,
+};
+
+const NUDGE_DISTANCE: f32 = 1.0;
+const LARGE_NUDGE_DISTANCE: f32 = 10.0;
+
+/// Moves the selection with the arrow keys, committed at once like a finished drag.
+pub(super) fn nudge_selection(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut selected_rect_query: Query<(&mut SelectedRect, &mut Transform), Without<Selected>>,
+    mut selected_query: Query<(Entity, &mut Selected, &mut Transform, Option<&Parent>)>,
+    parent_query: Query<&GlobalTransform>,
+    mut selection_moved_writer: EventWriter<SelectionMoved>,
+) {
+    let nudges = [
+        (Action::NudgeLeft, Vec2::NEG_X * NUDGE_DISTANCE),
+        (Action::NudgeRight, Vec2::X * NUDGE_DISTANCE),
+        (Action::NudgeUp, Vec2::Y * NUDGE_DISTANCE),
+        (Action::NudgeDown, Vec2::NEG_Y * NUDGE_DISTANCE),
+        (Action::NudgeLeftLarge, Vec2::NEG_X * LARGE_NUDGE_DISTANCE),
+        (Action::NudgeRightLarge, Vec2::X * LARGE_NUDGE_DISTANCE),
+        (Action::NudgeUpLarge, Vec2::Y * LARGE_NUDGE_DISTANCE),
+        (Action::NudgeDownLarge, Vec2::NEG_Y * LARGE_NUDGE_DISTANCE),
+    ];
+    let offset = nudges
+        .into_iter()
+        .filter(|(action, _)| keymap.just_pressed(*action, &keys))
+        .map(|(_, offset)| offset)
+        .sum::<Vec2>();
+
+    if offset == Vec2::ZERO || selected_query.is_empty() {
+        return;
+    }
+
+    let mut moved = Vec::new();
+    for (entity, mut selected, mut transform, parent) in &mut selected_query {
+        let parent = parent.and_then(|parent| parent_query.get(parent.get()).ok());
+        transform.translation += vector_to_parent_space(parent, offset).extend(0.);
+        selected.start_position = transform.translation.xy();
+        moved.push(entity);
+    }
+
+    if let Ok((mut selected_rect, mut transform)) = selected_rect_query.get_single_mut() {
+        transform.translation += offset.extend(0.);
+        selected_rect.move_to(transform.translation.xy());
+        selected_rect.commit();
+    }
+
+    selection_moved_writer.send(SelectionMoved { entities: moved });
+}
@@ -0,0 +1,337 @@
+use bevy::text::{Text2dBounds, TextLayoutInfo};
+use bevy_pancam::PanCam;
+
+use crate::{
+    canvas::CursorCoords,
+    item::ItemSize,
+    prelude::*,
+    rich_text::RichText,
+    text::{set_wrap_width, wrap_width, CanvasTextText},
+};
+
+use super::{
+    components::{Selected, SelectedRect},
+    utils::to_parent_space,
+    SelectionTransformed, SELECTED_RECT_COLOR,
+};
+
+/// Handle size in screen pixels, they keep their size when zooming.
+const HANDLE_SIZE: f32 = 12.0;
+const HANDLE_STROKE_WIDTH: f32 = 2.0;
+/// Items never shrink below this size, so they can still be grabbed.
+const MIN_ITEM_SIZE: f32 = 40.0;
+const MIN_SCALE: f32 = 0.05;
+
+/// Which corner or edge of the selected rect each handle sits on, as a unit direction from its
+/// centre.
+const HANDLE_DIRECTIONS: [Vec2; 8] = [
+    Vec2::new(-1., 1.),
+    Vec2::new(0., 1.),
+    Vec2::new(1., 1.),
+    Vec2::new(1., 0.),
+    Vec2::new(1., -1.),
+    Vec2::new(0., -1.),
+    Vec2::new(-1., -1.),
+    Vec2::new(-1., 0.),
+];
+
+#[derive(Component)]
+pub(super) struct ResizeHandle {
+    direction: Vec2,
+}
+
+/// The resize in progress, from pressing a handle until the mouse is released.
+#[derive(Resource, Default)]
+pub(super) struct Resizing(Option<Resize>);
+
+struct Resize {
+    direction: Vec2,
+    /// The selected rect when the resize started.
+    rect: Rect,
+    scale: Vec2,
+    items: Vec<ResizedItem>,
+}
+
+struct ResizedItem {
+    entity: Entity,
+    /// On the canvas, items in groups are placed back in the space of their group.
+    position: Vec2,
+    kind: ResizedKind,
+}
+
+enum ResizedKind {
+    /// Items with an [`ItemSize`].
+    Box(Vec2),
+    /// Canvas text, scaled by its font size or wrapped by its width.
+    Text {
+        entity: Entity,
+        /// The base size of its [`RichText`], sections are scaled from `section_sizes` while
+        /// dragging.
+        font_size: f32,
+        section_sizes: Vec<f32>,
+        width: f32,
+    },
+    /// Items that only move.
+    Fixed,
+}
+
+impl Resize {
+    /// The point that stays in place, opposite the dragged handle.
+    fn anchor(&self) -> Vec2 {
+        self.rect.center() - self.direction * self.rect.half_size()
+    }
+
+    /// How much text grows when dragged by a corner, or the top or bottom edge.
+    fn font_scale(&self) -> f32 {
+        if self.direction.x == 0. {
+            self.scale.y
+        } else {
+            self.scale.x.max(self.scale.y)
+        }
+    }
+}
+
+pub(super) fn not_resizing(resizing: Res<Resizing>) -> bool {
+    resizing.0.is_none()
+}
+
+pub(super) fn spawn_handles(builder: &mut ChildBuilder, rect: Rect) {
+    for direction in HANDLE_DIRECTIONS {
+        builder.spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Rectangle {
+                    extents: Vec2::splat(HANDLE_SIZE),
+                    origin: shapes::RectangleOrigin::Center,
+                }),
+                spatial: SpatialBundle::from_transform(Transform::from_translation(
+                    (direction * rect.half_size()).extend(1.),
+                )),
+                ..Default::default()
+            },
+            Fill::color(Color::WHITE),
+            Stroke::new(SELECTED_RECT_COLOR, HANDLE_STROKE_WIDTH),
+            ResizeHandle { direction },
+            Name::new("Resize Handle"),
+        ));
+    }
+}
+
+/// Keeps the handles on the corners and edges of the selected rect, at the same size on screen.
+pub(super) fn position_handles(
+    camera_query: Query<&OrthographicProjection, With<PanCam>>,
+    selected_rect_query: Query<(&SelectedRect, &Children)>,
+    mut handle_query: Query<(&ResizeHandle, &mut Transform)>,
+) {
+    let Ok((selected_rect, children)) = selected_rect_query.get_single() else {
+        return;
+    };
+    let zoom = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+    let half_size = selected_rect.rect().half_size();
+
+    for child in children {
+        if let Ok((handle, mut transform)) = handle_query.get_mut(*child) {
+            let translation = (handle.direction * half_size).extend(transform.translation.z);
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+            if transform.scale.x != zoom {
+                transform.scale = Vec3::splat(zoom);
+            }
+        }
+    }
+}
+
+fn handle_at(rect: Rect, position: Vec2, zoom: f32) -> Option<Vec2> {
+    HANDLE_DIRECTIONS.into_iter().find(|direction| {
+        Rect::from_center_half_size(
+            rect.center() + *direction * rect.half_size(),
+            Vec2::splat(HANDLE_SIZE * zoom),
+        )
+        .contains(position)
+    })
+}
+
+type ResizeTargetQuery = (
+    Entity,
+    &'static GlobalTransform,
+    Option<&'static ItemSize>,
+    Option<&'static Children>,
+);
+
+pub(super) fn start_resize(
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    camera_query: Query<&OrthographicProjection, With<PanCam>>,
+    selected_rect_query: Query<&SelectedRect>,
+    selected_query: Query<ResizeTargetQuery, With<Selected>>,
+    text_query: Query<
+        (Entity, &RichText, &Text, &Text2dBounds, &TextLayoutInfo),
+        With<CanvasTextText>,
+    >,
+    mut resizing: ResMut<Resizing>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok(selected_rect) = selected_rect_query.get_single() else {
+        return;
+    };
+    let zoom = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+    let rect = selected_rect.rect();
+    let Some(direction) = handle_at(rect, cursor_coords.current, zoom) else {
+        return;
+    };
+
+    let items = selected_query
+        .iter()
+        .map(|(entity, transform, size, children)| {
+            let text = children
+                .into_iter()
+                .flatten()
+                .find_map(|child| text_query.get(*child).ok());
+
+            let kind = match (size, text) {
+                (Some(size), _) => ResizedKind::Box(size.0),
+                (None, Some((text_entity, rich_text, text, bounds, layout))) => ResizedKind::Text {
+                    entity: text_entity,
+                    font_size: rich_text.font.size,
+                    section_sizes: text
+                        .sections
+                        .iter()
+                        .map(|section| section.style.font_size)
+                        .collect(),
+                    width: wrap_width(bounds).unwrap_or(layout.logical_size.x),
+                },
+                (None, None) => ResizedKind::Fixed,
+            };
+
+            ResizedItem {
+                entity,
+                position: transform.translation().xy(),
+                kind,
+            }
+        })
+        .collect();
+
+    resizing.0 = Some(Resize {
+        direction,
+        rect,
+        scale: Vec2::ONE,
+        items,
+    });
+}
+
+type ScaledItemQuery = (
+    &'static mut Transform,
+    Option<&'static mut ItemSize>,
+    Option<&'static Parent>,
+);
+
+/// Scales the selected items around the handle opposite the dragged one.
+/// Shift keeps the proportions of the selection.
+pub(super) fn resize_selection(
+    mouse_button_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor_coords: Res<CursorCoords>,
+    mut resizing: ResMut<Resizing>,
+    mut item_query: Query<ScaledItemQuery, With<Selected>>,
+    parent_query: Query<&GlobalTransform>,
+    mut text_query: Query<(&mut Text, &mut Text2dBounds, &mut RichText), With<CanvasTextText>>,
+    mut selection_transformed_writer: EventWriter<SelectionTransformed>,
+) {
+    if mouse_button_input.just_released(MouseButton::Left) {
+        // Font sizes are only committed at the end, so the edit is recorded once
+        if let Some(resize) = resizing.0.take() {
+            selection_transformed_writer.send(SelectionTransformed);
+            let font_scale = resize.font_scale();
+            for item in &resize.items {
+                if let ResizedKind::Text {
+                    entity, font_size, ..
+                } = item.kind
+                {
+                    if resize.direction.y == 0. || font_scale == 1. {
+                        continue;
+                    }
+                    if let Ok((_, _, mut rich_text)) = text_query.get_mut(entity) {
+                        rich_text.font.size = (font_size * font_scale).max(1.);
+                    }
+                }
+            }
+        }
+        return;
+    }
+
+    let Some(resize) = resizing.0.as_mut() else {
+        return;
+    };
+    if !mouse_button_input.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let anchor = resize.anchor();
+    let extents = resize.direction * resize.rect.size();
+    let dragged = resize.rect.center()
+        + resize.direction * resize.rect.half_size()
+        + cursor_coords.hold_distance();
+
+    let mut scale = Vec2::ONE;
+    for axis in 0..2 {
+        if resize.direction[axis] != 0. && extents[axis] != 0. {
+            scale[axis] = ((dragged[axis] - anchor[axis]) / extents[axis]).max(MIN_SCALE);
+        }
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        let uniform = match (resize.direction.x, resize.direction.y) {
+            (x, _) if x == 0. => scale.y,
+            (_, y) if y == 0. => scale.x,
+            _ => scale.x.max(scale.y),
+        };
+        scale = Vec2::splat(uniform);
+    }
+    if scale == resize.scale {
+        return;
+    }
+    resize.scale = scale;
+
+    for item in &resize.items {
+        let Ok((mut transform, size, parent)) = item_query.get_mut(item.entity) else {
+            continue;
+        };
+        let parent = parent.and_then(|parent| parent_query.get(parent.get()).ok());
+        let position = to_parent_space(parent, anchor + (item.position - anchor) * scale);
+        transform.translation = position.extend(transform.translation.z);
+
+        match &item.kind {
+            ResizedKind::Box(initial_size) => {
+                if let Some(mut size) = size {
+                    size.0 = (*initial_size * scale).max(Vec2::splat(MIN_ITEM_SIZE));
+                }
+            }
+            ResizedKind::Text {
+                entity,
+                section_sizes,
+                width,
+                ..
+            } => {
+                let Ok((mut text, mut bounds, _)) = text_query.get_mut(*entity) else {
+                    continue;
+                };
+
+                if resize.direction.y == 0. {
+                    let width = (*width * scale.x).max(MIN_ITEM_SIZE);
+                    set_wrap_width(&mut text, &mut bounds, Some(width));
+                } else {
+                    let font_scale = resize.font_scale();
+                    for (section, font_size) in text.sections.iter_mut().zip(section_sizes) {
+                        section.style.font_size = (font_size * font_scale).max(1.);
+                    }
+                }
+            }
+            ResizedKind::Fixed => {}
+        }
+    }
+}
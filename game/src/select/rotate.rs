@@ -0,0 +1,191 @@
+use std::f32::consts::PI;
+
+use bevy::render::primitives::Aabb;
+use bevy_pancam::PanCam;
+
+use crate::{canvas::CursorCoords, prelude::*};
+
+use super::{
+    components::{Selected, SelectedRect},
+    fit_selected_rect,
+    utils::{get_surrounding_rect, to_parent_space},
+    SelectionTransformed, SELECTED_RECT_COLOR,
+};
+
+/// Handle size and distance above the selected rect in screen pixels, like the resize handles.
+const HANDLE_RADIUS: f32 = 7.0;
+const HANDLE_OFFSET: f32 = 30.0;
+const HANDLE_STROKE_WIDTH: f32 = 2.0;
+const SNAP_ANGLE: f32 = PI / 12.;
+
+#[derive(Component)]
+pub(super) struct RotateHandle;
+
+/// The rotation in progress, from pressing the handle until the mouse is released.
+#[derive(Resource, Default)]
+pub(super) struct Rotating(Option<Rotation>);
+
+struct Rotation {
+    center: Vec2,
+    /// The angle from `center` to the cursor when the rotation started.
+    start_angle: f32,
+    /// Each item with its position on the canvas and its rotation.
+    items: Vec<(Entity, Vec2, Quat)>,
+}
+
+pub(super) fn not_rotating(rotating: Res<Rotating>) -> bool {
+    rotating.0.is_none()
+}
+
+pub(super) fn spawn_rotate_handle(builder: &mut ChildBuilder, rect: Rect) {
+    builder.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shapes::Circle {
+                radius: HANDLE_RADIUS,
+                center: Vec2::ZERO,
+            }),
+            spatial: SpatialBundle::from_transform(Transform::from_translation(
+                handle_position(rect, 1.).extend(1.),
+            )),
+            ..Default::default()
+        },
+        Fill::color(Color::WHITE),
+        Stroke::new(SELECTED_RECT_COLOR, HANDLE_STROKE_WIDTH),
+        RotateHandle,
+        Name::new("Rotate Handle"),
+    ));
+}
+
+/// Where the handle sits relative to the centre of `rect`, above its top edge.
+fn handle_position(rect: Rect, zoom: f32) -> Vec2 {
+    Vec2::new(0., rect.half_size().y + HANDLE_OFFSET * zoom)
+}
+
+pub(super) fn position_rotate_handle(
+    camera_query: Query<&OrthographicProjection, With<PanCam>>,
+    selected_rect_query: Query<(&SelectedRect, &Children)>,
+    mut handle_query: Query<&mut Transform, With<RotateHandle>>,
+) {
+    let Ok((selected_rect, children)) = selected_rect_query.get_single() else {
+        return;
+    };
+    let zoom = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+
+    for child in children {
+        if let Ok(mut transform) = handle_query.get_mut(*child) {
+            let translation =
+                handle_position(selected_rect.rect(), zoom).extend(transform.translation.z);
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+            if transform.scale.x != zoom {
+                transform.scale = Vec3::splat(zoom);
+            }
+        }
+    }
+}
+
+pub(super) fn start_rotate(
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    camera_query: Query<&OrthographicProjection, With<PanCam>>,
+    selected_rect_query: Query<&SelectedRect>,
+    selected_query: Query<(Entity, &GlobalTransform, &Transform), With<Selected>>,
+    mut rotating: ResMut<Rotating>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok(selected_rect) = selected_rect_query.get_single() else {
+        return;
+    };
+    let zoom = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+
+    let rect = selected_rect.rect();
+    let handle = rect.center() + handle_position(rect, zoom);
+    // A little larger than the handle, so it is easy to grab
+    if handle.distance(cursor_coords.current) > HANDLE_RADIUS * 2. * zoom {
+        return;
+    }
+
+    let offset = cursor_coords.current - rect.center();
+    rotating.0 = Some(Rotation {
+        center: rect.center(),
+        start_angle: offset.y.atan2(offset.x),
+        items: selected_query
+            .iter()
+            .map(|(entity, global_transform, transform)| {
+                (
+                    entity,
+                    global_transform.translation().xy(),
+                    transform.rotation,
+                )
+            })
+            .collect(),
+    });
+}
+
+type RotatedItemFilter = (With<Selected>, Without<SelectedRect>);
+
+/// Rotates the selected items around the centre of the selection.
+/// Shift snaps the rotation to steps of 15°.
+pub(super) fn rotate_selection(
+    mouse_button_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor_coords: Res<CursorCoords>,
+    mut rotating: ResMut<Rotating>,
+    mut item_query: Query<(&mut Transform, Option<&Parent>), RotatedItemFilter>,
+    parent_query: Query<&GlobalTransform>,
+    mut selected_rect_query: Query<(&mut SelectedRect, &mut Transform, &mut Path)>,
+    selected_query: Query<(&GlobalTransform, &Aabb), With<Selected>>,
+    mut selection_transformed_writer: EventWriter<SelectionTransformed>,
+) {
+    if rotating.0.is_none() {
+        return;
+    }
+
+    // The selected rect stays axis aligned, it is refit around the rotated items
+    if mouse_button_input.just_released(MouseButton::Left) {
+        rotating.0 = None;
+        selection_transformed_writer.send(SelectionTransformed);
+        if let Ok((mut selected_rect, mut transform, mut path)) =
+            selected_rect_query.get_single_mut()
+        {
+            transform.rotation = Quat::IDENTITY;
+            if let Some(rect) = get_surrounding_rect(selected_query.iter().collect()) {
+                fit_selected_rect(&mut selected_rect, &mut transform, &mut path, rect);
+            }
+        }
+        return;
+    }
+
+    let Some(rotation) = rotating.0.as_ref() else {
+        return;
+    };
+    let offset = cursor_coords.current - rotation.center;
+    let mut angle = offset.y.atan2(offset.x) - rotation.start_angle;
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        angle = (angle / SNAP_ANGLE).round() * SNAP_ANGLE;
+    }
+    let turn = Quat::from_rotation_z(angle);
+
+    for (entity, position, start_rotation) in &rotation.items {
+        if let Ok((mut transform, parent)) = item_query.get_mut(*entity) {
+            let parent = parent.and_then(|parent| parent_query.get(parent.get()).ok());
+            let position = to_parent_space(
+                parent,
+                rotation.center + (turn * (*position - rotation.center).extend(0.)).xy(),
+            );
+            transform.translation = position.extend(transform.translation.z);
+            transform.rotation = turn * *start_rotation;
+        }
+    }
+
+    if let Ok((_, mut transform, _)) = selected_rect_query.get_single_mut() {
+        transform.rotation = turn;
+    }
+}
@@ -12,14 +12,315 @@ pub(super) fn get_anchor(position: Vec2) -> shapes::RectangleOrigin {
     }
 }
 
-pub(super) fn get_surrounding_rect(query: Vec<(&GlobalTransform, &Aabb)>) -> Option<Rect> {
+/// The corners of an item's bounds on the canvas, following its rotation.
+pub(crate) fn get_corners(transform: &GlobalTransform, aabb: &Aabb) -> [Vec2; 4] {
+    let center = Vec3::from(aabb.center);
+    let half = Vec3::from(aabb.half_extents);
+
+    [
+        Vec3::new(-half.x, -half.y, 0.),
+        Vec3::new(half.x, -half.y, 0.),
+        Vec3::new(half.x, half.y, 0.),
+        Vec3::new(-half.x, half.y, 0.),
+    ]
+    .map(|corner| transform.transform_point(center + corner).xy())
+}
+
+/// Whether `position` is inside an item's bounds, following its rotation.
+pub(crate) fn contains_point(transform: &GlobalTransform, aabb: &Aabb, position: Vec2) -> bool {
+    let local = transform
+        .affine()
+        .inverse()
+        .transform_point3(position.extend(0.))
+        - Vec3::from(aabb.center);
+    let half = Vec3::from(aabb.half_extents);
+
+    local.x.abs() <= half.x && local.y.abs() <= half.y
+}
+
+/// Whether an item's rotated bounds overlap `rect`, by checking for a separating axis.
+pub(crate) fn intersects_rect(transform: &GlobalTransform, aabb: &Aabb, rect: Rect) -> bool {
+    let corners = get_corners(transform, aabb);
+    let rect_corners = [
+        rect.min,
+        Vec2::new(rect.max.x, rect.min.y),
+        rect.max,
+        Vec2::new(rect.min.x, rect.max.y),
+    ];
+    let axes = [
+        Vec2::X,
+        Vec2::Y,
+        (corners[1] - corners[0]).normalize_or_zero(),
+        (corners[3] - corners[0]).normalize_or_zero(),
+    ];
+
+    axes.into_iter().all(|axis| {
+        let project = |points: &[Vec2; 4]| {
+            points
+                .iter()
+                .map(|point| point.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                })
+        };
+        let (min, max) = project(&corners);
+        let (rect_min, rect_max) = project(&rect_corners);
+
+        min <= rect_max && rect_min <= max
+    })
+}
+
+pub(crate) fn get_surrounding_rect(query: Vec<(&GlobalTransform, &Aabb)>) -> Option<Rect> {
     let mut rect_option: Option<Rect> = None;
     for (transform, aabb) in query {
-        let rect =
-            Rect::from_center_half_size(transform.translation().xy(), aabb.half_extents.xy());
+        let corners = get_corners(transform, aabb);
+        let rect = corners.into_iter().fold(
+            Rect::from_corners(corners[0], corners[0]),
+            |rect, corner| rect.union_point(corner),
+        );
 
         rect_option = Some(rect_option.map_or(rect, |r| r.union(rect)));
     }
 
     rect_option
 }
+
+/// Whether an item's rotated bounds are entirely inside `rect`.
+pub(crate) fn contained_in_rect(transform: &GlobalTransform, aabb: &Aabb, rect: Rect) -> bool {
+    get_corners(transform, aabb)
+        .into_iter()
+        .all(|corner| rect.contains(corner))
+}
+
+/// Whether `point` is inside `polygon`, by counting the edges a ray to its right crosses.
+/// Points on an edge are inside, like they are for [`Rect::contains`].
+pub(crate) fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (index, a) in polygon.iter().enumerate() {
+        let b = polygon[(index + 1) % polygon.len()];
+        if on_segment(*a, b, point) {
+            return true;
+        }
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Whether an item's rotated bounds are entirely inside `polygon`.
+pub(crate) fn contained_in_polygon(
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    polygon: &[Vec2],
+) -> bool {
+    let corners = get_corners(transform, aabb);
+    corners
+        .iter()
+        .all(|corner| polygon_contains(polygon, *corner))
+        && !edges_cross(&corners, polygon)
+}
+
+/// Whether an item's rotated bounds overlap `polygon`.
+pub(crate) fn intersects_polygon(
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    polygon: &[Vec2],
+) -> bool {
+    let corners = get_corners(transform, aabb);
+    corners
+        .iter()
+        .any(|corner| polygon_contains(polygon, *corner))
+        || polygon
+            .iter()
+            .any(|point| contains_point(transform, aabb, *point))
+        || edges_cross(&corners, polygon)
+}
+
+/// Whether any edge of the closed shape `a` crosses any edge of the closed shape `b`.
+fn edges_cross(a: &[Vec2], b: &[Vec2]) -> bool {
+    let edges = |points: &[Vec2]| {
+        (0..points.len())
+            .map(|index| (points[index], points[(index + 1) % points.len()]))
+            .collect::<Vec<_>>()
+    };
+    let b_edges = edges(b);
+
+    edges(a).into_iter().any(|(a1, a2)| {
+        b_edges
+            .iter()
+            .any(|(b1, b2)| segments_cross(a1, a2, *b1, *b2))
+    })
+}
+
+/// Whether `point` lies on the segment from `a` to `b`.
+fn on_segment(a: Vec2, b: Vec2, point: Vec2) -> bool {
+    (b - a).perp_dot(point - a) == 0. && point.cmpge(a.min(b)).all() && point.cmple(a.max(b)).all()
+}
+
+/// Whether the segments cross each other. Segments that only touch, or run along each other,
+/// don't cross.
+fn segments_cross(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
+    let side = |from: Vec2, to: Vec2, point: Vec2| (to - from).perp_dot(point - from);
+
+    side(a1, a2, b1) * side(a1, a2, b2) < 0. && side(b1, b2, a1) * side(b1, b2, a2) < 0.
+}
+
+/// Converts a point on the canvas into the space of an item's parent, for items inside groups.
+pub(crate) fn to_parent_space(parent: Option<&GlobalTransform>, point: Vec2) -> Vec2 {
+    parent.map_or(point, |parent| {
+        parent
+            .affine()
+            .inverse()
+            .transform_point3(point.extend(0.))
+            .xy()
+    })
+}
+
+/// Converts a distance on the canvas into the space of an item's parent.
+pub(crate) fn vector_to_parent_space(parent: Option<&GlobalTransform>, vector: Vec2) -> Vec2 {
+    parent.map_or(vector, |parent| {
+        parent
+            .affine()
+            .inverse()
+            .transform_vector3(vector.extend(0.))
+            .xy()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    /// A U shape, open at the top between x = 10 and x = 20.
+    const U: [Vec2; 8] = [
+        Vec2::new(0., 0.),
+        Vec2::new(30., 0.),
+        Vec2::new(30., 30.),
+        Vec2::new(20., 30.),
+        Vec2::new(20., 10.),
+        Vec2::new(10., 10.),
+        Vec2::new(10., 30.),
+        Vec2::new(0., 30.),
+    ];
+
+    /// An item with the given half size, rotated about its center.
+    fn item(center: Vec2, half_size: Vec2, rotation: f32) -> (GlobalTransform, Aabb) {
+        let transform = Transform::from_translation(center.extend(0.))
+            .with_rotation(Quat::from_rotation_z(rotation));
+        let aabb = Aabb::from_min_max(-half_size.extend(0.), half_size.extend(0.));
+
+        (transform.into(), aabb)
+    }
+
+    #[test]
+    fn concave_polygons_leave_out_their_notch() {
+        assert!(polygon_contains(&U, Vec2::new(5., 20.)));
+        assert!(polygon_contains(&U, Vec2::new(25., 20.)));
+        assert!(polygon_contains(&U, Vec2::new(15., 5.)));
+        assert!(!polygon_contains(&U, Vec2::new(15., 20.)));
+        assert!(!polygon_contains(&U, Vec2::new(40., 20.)));
+    }
+
+    #[test]
+    fn points_on_an_edge_are_inside() {
+        let square = [
+            Vec2::new(0., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 10.),
+            Vec2::new(0., 10.),
+        ];
+
+        for point in [
+            Vec2::new(0., 5.),
+            Vec2::new(10., 5.),
+            Vec2::new(5., 0.),
+            Vec2::new(5., 10.),
+            Vec2::new(10., 10.),
+        ] {
+            assert!(polygon_contains(&square, point), "{point}");
+        }
+        assert!(polygon_contains(&U, Vec2::new(15., 10.)));
+        assert!(!polygon_contains(&square, Vec2::new(10.5, 5.)));
+    }
+
+    #[test]
+    fn segments_only_cross_through_each_other() {
+        let (a1, a2) = (Vec2::new(0., 0.), Vec2::new(10., 0.));
+
+        assert!(segments_cross(
+            a1,
+            a2,
+            Vec2::new(5., -5.),
+            Vec2::new(5., 5.)
+        ));
+        // Touching at an end
+        assert!(!segments_cross(
+            a1,
+            a2,
+            Vec2::new(5., 0.),
+            Vec2::new(5., 5.)
+        ));
+        // Collinear, overlapping and apart
+        assert!(!segments_cross(
+            a1,
+            a2,
+            Vec2::new(5., 0.),
+            Vec2::new(15., 0.)
+        ));
+        assert!(!segments_cross(
+            a1,
+            a2,
+            Vec2::new(20., 0.),
+            Vec2::new(30., 0.)
+        ));
+        // Parallel
+        assert!(!segments_cross(
+            a1,
+            a2,
+            Vec2::new(0., 1.),
+            Vec2::new(10., 1.)
+        ));
+    }
+
+    #[test]
+    fn items_across_a_notch_touch_the_lasso_but_are_not_inside_it() {
+        let (transform, aabb) = item(Vec2::new(15., 20.), Vec2::new(8., 2.), 0.);
+        assert!(intersects_polygon(&transform, &aabb, &U));
+        assert!(!contained_in_polygon(&transform, &aabb, &U));
+
+        let (transform, aabb) = item(Vec2::new(5., 20.), Vec2::new(2., 2.), FRAC_PI_4);
+        assert!(contained_in_polygon(&transform, &aabb, &U));
+
+        let (transform, aabb) = item(Vec2::new(15., 22.), Vec2::new(2., 2.), 0.);
+        assert!(!intersects_polygon(&transform, &aabb, &U));
+    }
+
+    #[test]
+    fn rotated_items_overlap_rects_without_a_corner_inside() {
+        // A diagonal bar through a flat one, like an X with no corner in the other shape
+        let (transform, aabb) = item(Vec2::ZERO, Vec2::new(50., 5.), FRAC_PI_4);
+        let rect = Rect::new(-50., -5., 50., 5.);
+
+        assert!(get_corners(&transform, &aabb)
+            .iter()
+            .all(|corner| !rect.contains(*corner)));
+        assert!(intersects_rect(&transform, &aabb, rect));
+        assert!(!contained_in_rect(&transform, &aabb, rect));
+    }
+
+    #[test]
+    fn rotated_items_miss_rects_inside_their_bounding_box() {
+        let (transform, aabb) = item(Vec2::new(40., 40.), Vec2::new(50., 5.), FRAC_PI_4);
+        let rect = Rect::new(50., 10., 70., 25.);
+
+        let bounds = get_surrounding_rect(vec![(&transform, &aabb)]).unwrap();
+        assert!(!bounds.intersect(rect).is_empty());
+        assert!(!intersects_rect(&transform, &aabb, rect));
+    }
+}
@@ -0,0 +1,311 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::render::primitives::Aabb;
+use bevy_pancam::PanCam;
+
+use crate::{
+    color_swatch::parse_color,
+    events::{AddItemEvent, ShapeEvent, ShapeKind},
+    item::{item_aabb, ItemBundle, ItemSize, SpawnItemEvent},
+    prelude::*,
+    select::components::Selected,
+};
+
+const SHAPE_SIZE: Vec2 = Vec2::new(300., 300.);
+const LINE_SIZE: Vec2 = Vec2::new(300., 0.);
+const DEFAULT_FILL: Color = Palette::SKY_200;
+const DEFAULT_STROKE: Color = Palette::SKY_700;
+const DEFAULT_STROKE_WIDTH: f32 = 4.0;
+const DEFAULT_CORNER_RADIUS: f32 = 32.0;
+const DEFAULT_STAR_POINTS: u32 = 5;
+const DEFAULT_POLYGON_SIDES: u32 = 6;
+/// How far the inner corners of a star are from its center, relative to the outer ones.
+const STAR_INNER_RADIUS: f32 = 0.45;
+/// Lines are thin, they can be picked this far around them.
+const LINE_HIT_SIZE: f32 = 24.0;
+
+pub struct ShapeItemPlugin;
+
+impl Plugin for ShapeItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                add_shape,
+                apply_shape_events,
+                redraw_shapes.after(add_shape).after(apply_shape_events),
+            ),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ShapeItem {
+    pub kind: ShapeKind,
+    pub fill: Color,
+    pub stroke: Color,
+    pub stroke_width: f32,
+    /// Ignored by ellipses and lines.
+    pub corner_radius: f32,
+}
+
+impl ShapeItem {
+    fn new(kind: ShapeKind) -> Self {
+        Self {
+            kind,
+            fill: if kind == ShapeKind::Line {
+                Color::NONE
+            } else {
+                DEFAULT_FILL
+            },
+            stroke: DEFAULT_STROKE,
+            stroke_width: DEFAULT_STROKE_WIDTH,
+            corner_radius: 0.,
+        }
+    }
+}
+
+pub(crate) fn spawn_shape(
+    commands: &mut Commands,
+    position: Vec3,
+    size: Vec2,
+    shape: ShapeItem,
+) -> Entity {
+    commands
+        .spawn((
+            ItemBundle {
+                fill: Fill::color(shape.fill),
+                stroke: shape_stroke(&shape),
+                shape: ShapeBundle {
+                    path: shape_path(&shape, size),
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ItemSize(size),
+            shape_aabb(&shape, size),
+            Name::new(match shape.kind {
+                ShapeKind::Rectangle => "Rectangle",
+                ShapeKind::Ellipse => "Ellipse",
+                ShapeKind::Triangle => "Triangle",
+                ShapeKind::Star { .. } => "Star",
+                ShapeKind::Polygon { .. } => "Polygon",
+                ShapeKind::Line => "Line",
+            }),
+            shape,
+        ))
+        .id()
+}
+
+fn add_shape(
+    mut commands: Commands,
+    mut events: EventReader<SpawnItemEvent>,
+    camera_query: Query<&Transform, With<PanCam>>,
+) {
+    for event in events.read() {
+        let shape = match event.item {
+            AddItemEvent::Ellipse => ShapeItem::new(ShapeKind::Ellipse),
+            AddItemEvent::RoundedRect => ShapeItem {
+                corner_radius: DEFAULT_CORNER_RADIUS,
+                ..ShapeItem::new(ShapeKind::Rectangle)
+            },
+            AddItemEvent::Triangle => ShapeItem::new(ShapeKind::Triangle),
+            AddItemEvent::Star(points) => ShapeItem::new(ShapeKind::Star {
+                points: if points < 3 {
+                    DEFAULT_STAR_POINTS
+                } else {
+                    points
+                },
+            }),
+            AddItemEvent::Line => ShapeItem::new(ShapeKind::Line),
+            AddItemEvent::Polygon(sides) => ShapeItem::new(ShapeKind::Polygon {
+                sides: if sides < 3 {
+                    DEFAULT_POLYGON_SIDES
+                } else {
+                    sides
+                },
+            }),
+            _ => continue,
+        };
+
+        // Place new shapes in the middle of the viewport
+        let position = event.position.unwrap_or_else(|| {
+            camera_query
+                .get_single()
+                .map(|transform| transform.translation.xy())
+                .unwrap_or_default()
+        });
+        let size = if shape.kind == ShapeKind::Line {
+            LINE_SIZE
+        } else {
+            SHAPE_SIZE
+        };
+
+        spawn_shape(&mut commands, position.extend(0.), size, shape);
+    }
+}
+
+fn apply_shape_events(
+    mut events: EventReader<ShapeEvent>,
+    mut shape_query: Query<&mut ShapeItem, With<Selected>>,
+) {
+    for event in events.read() {
+        let color = match event {
+            ShapeEvent::Fill(color) if color.trim().eq_ignore_ascii_case("none") => {
+                Some(Color::NONE)
+            }
+            ShapeEvent::Fill(color) | ShapeEvent::Stroke(color) => match parse_color(color) {
+                Some(color) => Some(color),
+                None => {
+                    warn!("Unsupported shape color {}", color);
+                    continue;
+                }
+            },
+            ShapeEvent::StrokeWidth(_) | ShapeEvent::CornerRadius(_) => None,
+        };
+
+        for mut shape in &mut shape_query {
+            let mut restyled = *shape;
+            match (event, color) {
+                (ShapeEvent::Fill(_), Some(color)) => restyled.fill = color,
+                (ShapeEvent::Stroke(_), Some(color)) => restyled.stroke = color,
+                (ShapeEvent::StrokeWidth(width), _) => restyled.stroke_width = width.max(0.),
+                (ShapeEvent::CornerRadius(radius), _) => restyled.corner_radius = radius.max(0.),
+                _ => {}
+            }
+            // Shapes that already look like this aren't recorded as edits
+            shape.set_if_neq(restyled);
+        }
+    }
+}
+
+type ShapeDrawingQuery = (
+    Entity,
+    &'static ShapeItem,
+    &'static ItemSize,
+    &'static mut Path,
+    &'static mut Fill,
+    &'static mut Stroke,
+);
+
+type ShapeChangedFilter = Or<(Changed<ShapeItem>, Changed<ItemSize>)>;
+
+/// Redraws shapes that were resized or restyled.
+fn redraw_shapes(
+    mut commands: Commands,
+    mut shape_query: Query<ShapeDrawingQuery, ShapeChangedFilter>,
+) {
+    for (entity, shape, size, mut path, mut fill, mut stroke) in &mut shape_query {
+        *path = shape_path(shape, size.0);
+        *fill = Fill::color(shape.fill);
+        *stroke = shape_stroke(shape);
+        // Bounds are only calculated once by bevy
+        commands.entity(entity).insert(shape_aabb(shape, size.0));
+    }
+}
+
+fn shape_stroke(shape: &ShapeItem) -> Stroke {
+    Stroke {
+        color: shape.stroke,
+        options: StrokeOptions::default()
+            .with_line_width(shape.stroke_width)
+            .with_line_cap(LineCap::Round)
+            .with_line_join(LineJoin::Round),
+    }
+}
+
+fn shape_aabb(shape: &ShapeItem, size: Vec2) -> Aabb {
+    if shape.kind == ShapeKind::Line {
+        item_aabb(size.max(Vec2::splat(LINE_HIT_SIZE)))
+    } else {
+        item_aabb(size)
+    }
+}
+
+fn shape_path(shape: &ShapeItem, size: Vec2) -> Path {
+    let half_size = size / 2.;
+    match shape.kind {
+        ShapeKind::Ellipse => GeometryBuilder::build_as(&shapes::Ellipse {
+            radii: half_size,
+            center: Vec2::ZERO,
+        }),
+        ShapeKind::Line => {
+            let mut builder = PathBuilder::new();
+            builder.move_to(-half_size);
+            builder.line_to(half_size);
+            builder.build()
+        }
+        kind => {
+            let corners = unit_corners(kind)
+                .into_iter()
+                .map(|corner| corner * half_size)
+                .collect::<Vec<_>>();
+            rounded_polygon_path(&corners, shape.corner_radius)
+        }
+    }
+}
+
+/// The corners of a polygonal shape, counterclockwise within a box from -1 to 1.
+fn unit_corners(kind: ShapeKind) -> Vec<Vec2> {
+    match kind {
+        ShapeKind::Rectangle => vec![
+            Vec2::new(-1., -1.),
+            Vec2::new(1., -1.),
+            Vec2::new(1., 1.),
+            Vec2::new(-1., 1.),
+        ],
+        ShapeKind::Triangle => vec![Vec2::new(-1., -1.), Vec2::new(1., -1.), Vec2::new(0., 1.)],
+        ShapeKind::Star { points } => corners_around(points.max(3) * 2, |index| {
+            if index % 2 == 0 {
+                1.
+            } else {
+                STAR_INNER_RADIUS
+            }
+        }),
+        ShapeKind::Polygon { sides } => corners_around(sides.max(3), |_| 1.),
+        ShapeKind::Ellipse | ShapeKind::Line => Vec::new(),
+    }
+}
+
+/// Corners spread evenly around the center, the first one at the top.
+fn corners_around(count: u32, radius: impl Fn(u32) -> f32) -> Vec<Vec2> {
+    (0..count)
+        .map(|index| {
+            let angle = FRAC_PI_2 + index as f32 * TAU / count as f32;
+            Vec2::from_angle(angle) * radius(index)
+        })
+        .collect()
+}
+
+/// A closed path through the corners, each rounded by up to `radius`.
+/// Radii are capped at half the shorter edge next to the corner, so the curves never overlap.
+fn rounded_polygon_path(corners: &[Vec2], radius: f32) -> Path {
+    let mut builder = PathBuilder::new();
+    let count = corners.len();
+    if count < 3 {
+        return builder.build();
+    }
+
+    for (index, corner) in corners.iter().enumerate() {
+        let before = corners[(index + count - 1) % count];
+        let after = corners[(index + 1) % count];
+        let radius = radius
+            .min(corner.distance(before) / 2.)
+            .min(corner.distance(after) / 2.);
+
+        let start = *corner + (before - *corner).normalize_or_zero() * radius;
+        let end = *corner + (after - *corner).normalize_or_zero() * radius;
+        if index == 0 {
+            builder.move_to(start);
+        } else {
+            builder.line_to(start);
+        }
+        if radius > 0. {
+            builder.quadratic_bezier_to(*corner, end);
+        }
+    }
+    builder.close();
+
+    builder.build()
+}
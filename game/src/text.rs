@@ -1,11 +1,14 @@
-use bevy::text::{BreakLineOn, TextLayoutInfo};
+use bevy::text::{BreakLineOn, Text2dBounds, TextLayoutInfo};
 
 use crate::{
+    document::FontDocument,
     events::AddItemEvent,
-    item::ItemBundle,
+    item::{item_aabb, ItemBundle, SpawnItemEvent},
     prelude::*,
+    rich_text::RichText,
+    text_edit::TextEditor,
     theme::{Theme, ThemeDidChange},
-    FontStack,
+    FontFamilyKind, FontStack, FontVariant,
 };
 
 const TEXT_SELECTED_PADDING: Vec2 = Vec2::new(20., 20.);
@@ -27,18 +30,18 @@ pub struct CanvasTextText;
 pub(crate) fn spawn_text(
     commands: &mut Commands,
     theme: &Theme,
+    font_stack: &FontStack,
     position: Vec3,
-    text: impl Into<String>,
-    size: f32,
-    font: Handle<Font>,
-) {
-    let text_style = TextStyle {
-        font: font,
-        font_size: size,
-        color: theme.default_text_color,
-        ..Default::default()
+    rich_text: RichText,
+    wrap_width: Option<f32>,
+) -> Entity {
+    let mut text = Text {
+        sections: rich_text.sections(font_stack, theme.default_text_color),
+        alignment: TextAlignment::Left,
+        linebreak_behavior: BreakLineOn::NoWrap,
     };
-
+    let mut text_2d_bounds = Text2dBounds::default();
+    set_wrap_width(&mut text, &mut text_2d_bounds, wrap_width);
     commands
         .spawn((
             ItemBundle {
@@ -59,17 +62,35 @@ pub(crate) fn spawn_text(
         .with_children(|builder| {
             builder.spawn((
                 Text2dBundle {
-                    text: Text {
-                        sections: vec![TextSection::new(text, text_style.clone())],
-                        alignment: TextAlignment::Left,
-                        linebreak_behavior: BreakLineOn::NoWrap,
-                    },
+                    text,
+                    text_2d_bounds,
                     ..default()
                 },
+                rich_text,
                 CanvasTextText,
                 Name::new("Canvas Text Text"),
             ));
-        });
+        })
+        .id()
+}
+
+/// Wraps canvas text at `width`, without one the text grows to fit its lines.
+pub(crate) fn set_wrap_width(text: &mut Text, bounds: &mut Text2dBounds, width: Option<f32>) {
+    match width {
+        Some(width) => {
+            bounds.size.x = width;
+            text.linebreak_behavior = BreakLineOn::WordBoundary;
+        }
+        None => {
+            bounds.size.x = f32::INFINITY;
+            text.linebreak_behavior = BreakLineOn::NoWrap;
+        }
+    }
+}
+
+/// The width canvas text wraps at, if any.
+pub(crate) fn wrap_width(bounds: &Text2dBounds) -> Option<f32> {
+    bounds.size.x.is_finite().then_some(bounds.size.x)
 }
 
 fn update_textbox_size(
@@ -87,43 +108,56 @@ fn update_textbox_size(
             continue;
         }
 
+        let extents = text_info.logical_size + TEXT_SELECTED_PADDING;
         let new_path: Path = GeometryBuilder::build_as(&shapes::Rectangle {
-            extents: text_info.logical_size + TEXT_SELECTED_PADDING,
+            extents,
             ..Default::default()
         });
         commands.entity(parent.get()).remove::<Path>();
-        commands.entity(parent.get()).insert(new_path);
+        commands
+            .entity(parent.get())
+            .insert((new_path, item_aabb(extents)));
     }
 }
 
+/// Editors render their own sections.
+type CanvasTextFilter = (With<CanvasTextText>, Without<TextEditor>);
+
 fn on_theme_change(
     mut theme_event_reader: EventReader<ThemeDidChange>,
-    mut text_query: Query<&mut Text, With<CanvasTextText>>,
+    font_stack: Res<FontStack>,
+    mut text_query: Query<(&RichText, &mut Text), CanvasTextFilter>,
 ) {
     for event in theme_event_reader.read() {
-        for mut text in text_query.iter_mut() {
-            for section in text.sections.iter_mut() {
-                section.style.color = event.theme.default_text_color
-            }
+        // Rebuilt from the spans, so explicitly colored spans keep their color
+        for (rich_text, mut text) in text_query.iter_mut() {
+            text.sections = rich_text.sections(&font_stack, event.theme.default_text_color);
         }
     }
 }
 
 fn add_text(
     mut commands: Commands,
-    mut events: EventReader<AddItemEvent>,
+    mut events: EventReader<SpawnItemEvent>,
     theme: Res<Theme>,
     font_stack: Res<FontStack>,
 ) {
     for event in events.read() {
-        if let AddItemEvent::Text(value) = event {
+        if let AddItemEvent::Text(value) = &event.item {
             spawn_text(
                 &mut commands,
                 &theme,
-                Vec3::new(0., 0., 0.0),
-                value.clone(),
-                font_stack.size.large,
-                font_stack.body.regular().clone(),
+                &font_stack,
+                event.position.unwrap_or_default().extend(0.),
+                RichText::plain(
+                    value.clone(),
+                    FontDocument {
+                        family: FontFamilyKind::Body,
+                        variant: FontVariant::Regular,
+                        size: font_stack.size.large,
+                    },
+                ),
+                None,
             );
         }
     }
@@ -0,0 +1,740 @@
+use std::ops::Range;
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    render::primitives::Aabb,
+};
+
+use crate::{
+    canvas::CursorCoords,
+    color_swatch::parse_color,
+    connector::ConnectorLabel,
+    drawing::not_drawing,
+    events::{OutputEvent, PasteEvent, SpanColorEvent, TxOutputEvent},
+    frame::FrameTitle,
+    item::Item,
+    post_it::PostItText,
+    prelude::*,
+    rich_text::{RichText, SpanStyle},
+    select::{components::Selectable, utils::contains_point},
+    text::CanvasTextText,
+    theme::Theme,
+    FontStack,
+};
+
+const DOUBLE_CLICK_SECONDS: f64 = 0.4;
+const DOUBLE_CLICK_DISTANCE: f32 = 10.0;
+const CARET_BLINK_SECONDS: f32 = 0.5;
+const CARET: &str = "|";
+const CARET_COLOR: Color = Palette::PURPLE_600;
+const SELECTION_COLOR: Color = Palette::BLUE_500;
+
+pub struct TextEditPlugin;
+
+impl Plugin for TextEditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                commit_on_click_away,
+                start_editing
+                    .run_if(not_drawing)
+                    .after(commit_on_click_away),
+                edit_text.after(start_editing),
+                color_spans.after(start_editing),
+                render_editors.after(edit_text).after(color_spans),
+                report_editing.after(edit_text),
+            ),
+        );
+    }
+}
+
+/// Edit state of a post-it or canvas text while it is being typed in.
+/// Lives on the text entity, whose sections only display the caret and selection until the
+/// edit is committed.
+#[derive(Component)]
+pub(crate) struct TextEditor {
+    rich_text: RichText,
+    /// The plain text of `rich_text`.
+    text: String,
+    /// Byte offsets into `text`, always on char boundaries.
+    caret: usize,
+    anchor: usize,
+    /// Style toggled without a selection, applied to what is typed next.
+    typing_style: Option<SpanStyle>,
+    blink: Timer,
+    caret_visible: bool,
+}
+
+impl TextEditor {
+    fn new(rich_text: RichText) -> Self {
+        let text = rich_text.text();
+        let caret = text.len();
+
+        Self {
+            rich_text,
+            text,
+            caret,
+            anchor: caret,
+            typing_style: None,
+            blink: Timer::from_seconds(CARET_BLINK_SECONDS, TimerMode::Repeating),
+            caret_visible: true,
+        }
+    }
+
+    pub fn rich_text(&self) -> &RichText {
+        &self.rich_text
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    fn insert(&mut self, value: &str) {
+        let selection = self.selection();
+        // Replacements take the style of the text they replace
+        let style = self.typing_style.take().unwrap_or_else(|| {
+            self.rich_text
+                .style_at(selection.start + usize::from(!selection.is_empty()))
+        });
+
+        self.rich_text.replace(selection.clone(), value, style);
+        self.text = self.rich_text.text();
+        self.caret = selection.start + value.len();
+        self.anchor = self.caret;
+    }
+
+    /// The style text typed at the caret gets.
+    fn current_style(&self) -> SpanStyle {
+        self.typing_style
+            .unwrap_or_else(|| self.rich_text.style_at(self.caret))
+    }
+
+    /// Turns a style on for the selection, or off if all of it has it already.
+    fn toggle_style(&mut self, is_set: fn(&SpanStyle) -> bool, set: fn(&mut SpanStyle, bool)) {
+        let selection = self.selection();
+        if selection.is_empty() {
+            let mut style = self.current_style();
+            set(&mut style, !is_set(&style));
+            self.typing_style = Some(style);
+        } else {
+            let value = !self.rich_text.all(selection.clone(), is_set);
+            self.rich_text
+                .update_style(selection, |style| set(style, value));
+        }
+    }
+
+    /// Moves the selection one step up or down the [`FontSizeMap`](crate::FontSizeMap).
+    fn step_size(&mut self, font_stack: &FontStack, larger: bool) {
+        let base = font_stack.size.nearest(self.rich_text.font.size);
+        let step = |style: &mut SpanStyle| {
+            let size = style.size.unwrap_or(base);
+            style.size = Some(if larger {
+                size.larger()
+            } else {
+                size.smaller()
+            });
+        };
+
+        let selection = self.selection();
+        if selection.is_empty() {
+            let mut style = self.current_style();
+            step(&mut style);
+            self.typing_style = Some(style);
+        } else {
+            self.rich_text.update_style(selection, step);
+        }
+    }
+
+    /// Colors the selection, or what is typed next. `None` follows the theme's text color.
+    fn set_color(&mut self, color: Option<Color>) {
+        let selection = self.selection();
+        if selection.is_empty() {
+            let mut style = self.current_style();
+            style.color = color;
+            self.typing_style = Some(style);
+        } else {
+            self.rich_text
+                .update_style(selection, |style| style.color = color);
+        }
+    }
+
+    fn delete_backward(&mut self, word: bool) {
+        if self.selection().is_empty() {
+            self.anchor = if word {
+                self.previous_word(self.caret)
+            } else {
+                self.previous_char(self.caret)
+            };
+        }
+        self.insert("");
+    }
+
+    fn delete_forward(&mut self, word: bool) {
+        if self.selection().is_empty() {
+            self.anchor = if word {
+                self.next_word(self.caret)
+            } else {
+                self.next_char(self.caret)
+            };
+        }
+        self.insert("");
+    }
+
+    fn move_to(&mut self, position: usize, extend: bool) {
+        self.typing_style = None;
+        self.caret = position;
+        if !extend {
+            self.anchor = position;
+        }
+    }
+
+    fn select_all(&mut self) {
+        self.typing_style = None;
+        self.anchor = 0;
+        self.caret = self.text.len();
+    }
+
+    fn previous_char(&self, position: usize) -> usize {
+        self.text[..position]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_char(&self, position: usize) -> usize {
+        self.text[position..]
+            .chars()
+            .next()
+            .map_or(position, |char| position + char.len_utf8())
+    }
+
+    /// The start of the word before `position`, skipping any whitespace in between.
+    fn previous_word(&self, position: usize) -> usize {
+        self.text[..position]
+            .trim_end()
+            .char_indices()
+            .rev()
+            .find(|(_, char)| char.is_whitespace())
+            .map_or(0, |(index, char)| index + char.len_utf8())
+    }
+
+    /// The end of the word after `position`, skipping any whitespace in between.
+    fn next_word(&self, position: usize) -> usize {
+        let after = &self.text[position..];
+        let whitespace = after.len() - after.trim_start().len();
+        let word = after[whitespace..]
+            .find(char::is_whitespace)
+            .unwrap_or(after.len() - whitespace);
+
+        position + whitespace + word
+    }
+
+    fn line_start(&self, position: usize) -> usize {
+        self.text[..position]
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    fn line_end(&self, position: usize) -> usize {
+        self.text[position..]
+            .find('\n')
+            .map_or(self.text.len(), |index| position + index)
+    }
+
+    fn line_above(&self, position: usize) -> usize {
+        let start = self.line_start(position);
+        if start == 0 {
+            return 0;
+        }
+
+        let column = self.text[start..position].chars().count();
+        let above_end = start - 1;
+        self.column_in_line(self.line_start(above_end), above_end, column)
+    }
+
+    fn line_below(&self, position: usize) -> usize {
+        let end = self.line_end(position);
+        if end == self.text.len() {
+            return end;
+        }
+
+        let column = self.text[self.line_start(position)..position]
+            .chars()
+            .count();
+        let below_start = end + 1;
+        self.column_in_line(below_start, self.line_end(below_start), column)
+    }
+
+    /// The offset `column` chars into the line, or its end if the line is shorter.
+    fn column_in_line(&self, start: usize, end: usize, column: usize) -> usize {
+        self.text[start..end]
+            .char_indices()
+            .nth(column)
+            .map_or(end, |(index, _)| start + index)
+    }
+
+    fn restart_blink(&mut self) {
+        self.blink.reset();
+        self.caret_visible = true;
+    }
+
+    /// The spans split around the selection, with the caret drawn in between.
+    fn sections(&self, font_stack: &FontStack, text_color: Color) -> Vec<TextSection> {
+        let selection = self.selection();
+        let caret = TextSection::new(
+            CARET,
+            TextStyle {
+                color: if self.caret_visible {
+                    CARET_COLOR
+                } else {
+                    Palette::TRANSPARENT
+                },
+                ..self
+                    .rich_text
+                    .text_style(&self.current_style(), font_stack, text_color)
+            },
+        );
+
+        let mut sections = Vec::new();
+        let mut caret_drawn = false;
+        let mut offset = 0;
+        for span in &self.rich_text.spans {
+            let style = self
+                .rich_text
+                .text_style(&span.style, font_stack, text_color);
+            let end = offset + span.text.len();
+
+            let mut cuts = [selection.start, selection.end, self.caret]
+                .into_iter()
+                .filter(|cut| offset < *cut && *cut < end)
+                .chain([offset, end])
+                .collect::<Vec<_>>();
+            cuts.sort_unstable();
+            cuts.dedup();
+
+            for piece in cuts.windows(2) {
+                let (start, end) = (piece[0], piece[1]);
+                if start == self.caret && !caret_drawn {
+                    sections.push(caret.clone());
+                    caret_drawn = true;
+                }
+
+                let color = if selection.contains(&start) {
+                    SELECTION_COLOR
+                } else {
+                    style.color
+                };
+                sections.push(TextSection::new(
+                    &self.text[start..end],
+                    TextStyle {
+                        color,
+                        ..style.clone()
+                    },
+                ));
+            }
+            offset = end;
+        }
+        if !caret_drawn {
+            sections.push(caret);
+        }
+
+        sections
+    }
+}
+
+/// Run condition for keyboard shortcuts that would get in the way of typing.
+pub(crate) fn not_editing_text(editor_query: Query<(), With<TextEditor>>) -> bool {
+    editor_query.is_empty()
+}
+
+fn commit_on_click_away(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    editor_query: Query<(Entity, &TextEditor, &Parent)>,
+    item_query: Query<(&GlobalTransform, &Aabb)>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    for (entity, editor, parent) in &editor_query {
+        let clicked_inside = item_query
+            .get(parent.get())
+            .is_ok_and(|(transform, aabb)| contains_point(transform, aabb, cursor_coords.current));
+        if !clicked_inside {
+            commit(&mut commands, entity, editor);
+        }
+    }
+}
+
+/// Whether a click at `position` completes a double click, given the time and position of the
+/// previous click, which it updates.
+pub(crate) fn is_double_click(
+    last_click: &mut Option<(f64, Vec2)>,
+    now: f64,
+    position: Vec2,
+) -> bool {
+    let is_double_click = last_click.is_some_and(|(time, last_position)| {
+        now - time < DOUBLE_CLICK_SECONDS
+            && last_position.distance(position) < DOUBLE_CLICK_DISTANCE
+    });
+    // A third click starts a new double click
+    *last_click = (!is_double_click).then_some((now, position));
+
+    is_double_click
+}
+
+/// Text in a group is only edited once the group is entered.
+type EditableItemFilter = (With<Item>, With<Selectable>);
+
+type EditableTextFilter = Or<(
+    With<PostItText>,
+    With<CanvasTextText>,
+    With<FrameTitle>,
+    With<ConnectorLabel>,
+)>;
+
+fn start_editing(
+    mut commands: Commands,
+    time: Res<Time>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_coords: Res<CursorCoords>,
+    item_query: Query<(&GlobalTransform, &Aabb, &Children), EditableItemFilter>,
+    text_query: Query<(Entity, &RichText, Has<TextEditor>), EditableTextFilter>,
+    mut last_click: Local<Option<(f64, Vec2)>>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let position = cursor_coords.current;
+    if !is_double_click(&mut last_click, time.elapsed_seconds_f64(), position) {
+        return;
+    }
+
+    let topmost_text = item_query
+        .iter()
+        .filter(|(transform, aabb, _)| contains_point(transform, aabb, position))
+        .filter_map(|(transform, _, children)| {
+            let text = children
+                .iter()
+                .find_map(|child| text_query.get(*child).ok())?;
+            Some((transform.translation().z, text))
+        })
+        .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let Some((_, (entity, rich_text, is_editing))) = topmost_text else {
+        return;
+    };
+    if is_editing {
+        return;
+    }
+
+    commands
+        .entity(entity)
+        .insert(TextEditor::new(rich_text.clone()));
+}
+
+fn edit_text(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    font_stack: Res<FontStack>,
+    tx_output_event: Res<TxOutputEvent>,
+    mut keyboard_reader: EventReader<KeyboardInput>,
+    mut character_reader: EventReader<ReceivedCharacter>,
+    mut paste_reader: EventReader<PasteEvent>,
+    mut editor_query: Query<(Entity, &mut TextEditor)>,
+) {
+    let Ok((entity, mut editor)) = editor_query.get_single_mut() else {
+        keyboard_reader.clear();
+        character_reader.clear();
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        commit(&mut commands, entity, &editor);
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let command = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    // Ctrl moves by words on most platforms, Alt (Option) does on macOS
+    let word = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::AltLeft,
+        KeyCode::AltRight,
+    ]);
+
+    // Key presses repeat while held, unlike `Input::just_pressed`
+    for event in keyboard_reader.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let Some(key_code) = event.key_code else {
+            continue;
+        };
+
+        let caret = editor.caret;
+        match key_code {
+            KeyCode::Left if word => {
+                let position = editor.previous_word(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Left => {
+                let position = editor.previous_char(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Right if word => {
+                let position = editor.next_word(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Right => {
+                let position = editor.next_char(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Up => {
+                let position = editor.line_above(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Down => {
+                let position = editor.line_below(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Home => {
+                let position = editor.line_start(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::End => {
+                let position = editor.line_end(caret);
+                editor.move_to(position, shift);
+            }
+            KeyCode::Back => editor.delete_backward(word),
+            KeyCode::Delete => editor.delete_forward(word),
+            KeyCode::Return | KeyCode::NumpadEnter => editor.insert("\n"),
+            KeyCode::A if command => editor.select_all(),
+            KeyCode::B if command => {
+                editor.toggle_style(|style| style.bold, |style, bold| style.bold = bold)
+            }
+            KeyCode::I if command => {
+                editor.toggle_style(|style| style.italic, |style, italic| style.italic = italic)
+            }
+            KeyCode::Period if command && shift => editor.step_size(&font_stack, true),
+            KeyCode::Comma if command && shift => editor.step_size(&font_stack, false),
+            KeyCode::C | KeyCode::X if command => {
+                if !editor.selection().is_empty() {
+                    tx_output_event.emit(OutputEvent::CopyToClipboard(
+                        editor.selected_text().to_string(),
+                    ));
+                    if key_code == KeyCode::X {
+                        editor.insert("");
+                    }
+                }
+            }
+            _ => continue,
+        }
+        editor.restart_blink();
+    }
+
+    for event in character_reader.read() {
+        // Shortcuts and keys like Backspace arrive as characters too
+        if command || event.char.is_control() {
+            continue;
+        }
+
+        let mut buffer = [0; 4];
+        editor.insert(event.char.encode_utf8(&mut buffer));
+        editor.restart_blink();
+    }
+
+    for event in paste_reader.read() {
+        if let PasteEvent::Text(value) = event {
+            editor.insert(value);
+            editor.restart_blink();
+        }
+    }
+}
+
+fn color_spans(mut events: EventReader<SpanColorEvent>, mut editor_query: Query<&mut TextEditor>) {
+    for event in events.read() {
+        let color = if event.color.trim().eq_ignore_ascii_case("none") {
+            None
+        } else {
+            match parse_color(&event.color) {
+                Some(color) => Some(color),
+                None => {
+                    warn!("Unsupported text color {}", event.color);
+                    continue;
+                }
+            }
+        };
+
+        for mut editor in &mut editor_query {
+            editor.set_color(color);
+        }
+    }
+}
+
+fn render_editors(
+    time: Res<Time>,
+    theme: Res<Theme>,
+    font_stack: Res<FontStack>,
+    mut editor_query: Query<(&mut TextEditor, &mut Text)>,
+) {
+    for (mut editor, mut text) in &mut editor_query {
+        // Ticking alone isn't a change, the sections are only rebuilt when the caret blinks
+        let blinked = editor
+            .bypass_change_detection()
+            .blink
+            .tick(time.delta())
+            .just_finished();
+        if blinked {
+            editor.caret_visible = !editor.caret_visible;
+        }
+
+        if editor.is_changed() || theme.is_changed() {
+            text.sections = editor.sections(&font_stack, theme.default_text_color);
+        }
+    }
+}
+
+fn report_editing(
+    editor_query: Query<(), With<TextEditor>>,
+    tx_output_event: Res<TxOutputEvent>,
+    mut was_editing: Local<bool>,
+) {
+    let is_editing = !editor_query.is_empty();
+    if is_editing != *was_editing {
+        *was_editing = is_editing;
+        tx_output_event.emit(OutputEvent::TextEditingChanged(is_editing));
+    }
+}
+
+/// Writes the edited spans back, their `Text` sections are rebuilt from them.
+fn commit(commands: &mut Commands, entity: Entity, editor: &TextEditor) {
+    commands
+        .entity(entity)
+        .insert(editor.rich_text.clone())
+        .remove::<TextEditor>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::FontDocument, FontFamilyKind, FontVariant};
+
+    /// Lines of "naïve café", "über  straße" and "日本", with chars of two and three bytes.
+    const TEXT: &str = "naïve café\nüber  straße\n日本";
+
+    fn editor(text: &str) -> TextEditor {
+        TextEditor::new(RichText::plain(
+            text,
+            FontDocument {
+                family: FontFamilyKind::Body,
+                variant: FontVariant::Regular,
+                size: 16.,
+            },
+        ))
+    }
+
+    /// The byte offset of `needle` in [`TEXT`].
+    fn at(needle: &str) -> usize {
+        TEXT.find(needle).unwrap()
+    }
+
+    #[test]
+    fn words_are_found_across_multi_byte_chars() {
+        let editor = editor(TEXT);
+
+        // From inside a word to its start, then on to the start of the word before
+        assert_eq!(editor.previous_word(at("fé")), at("café"));
+        assert_eq!(editor.previous_word(at("café")), at("naïve"));
+        // Line breaks and runs of whitespace are skipped
+        assert_eq!(editor.previous_word(at("über")), at("café"));
+        assert_eq!(editor.previous_word(at("straße")), at("über"));
+        assert_eq!(editor.previous_word(0), 0);
+
+        assert_eq!(editor.next_word(0), at(" café"));
+        assert_eq!(editor.next_word(at(" café")), at("\nüber"));
+        assert_eq!(editor.next_word(at("  straße")), at("\n日本"));
+        assert_eq!(editor.next_word(at("日本")), TEXT.len());
+        assert_eq!(editor.next_word(TEXT.len()), TEXT.len());
+    }
+
+    #[test]
+    fn lines_above_and_below_keep_the_column_in_chars() {
+        let editor = editor(TEXT);
+
+        // Three chars into the line, after "übe" and "naï"
+        assert_eq!(editor.line_above(at("r  straße")), at("ve café"));
+        assert_eq!(editor.line_below(at("ve café")), at("r  straße"));
+        assert_eq!(editor.line_above(at("本")), at("ber"));
+
+        // Shorter lines put the caret at their end
+        assert_eq!(editor.line_below(at("straße")), TEXT.len());
+
+        // The first and last lines go to the start and end of the text
+        assert_eq!(editor.line_above(at("café")), 0);
+        assert_eq!(editor.line_below(at("本")), TEXT.len());
+    }
+
+    #[test]
+    fn deleting_removes_whole_chars_and_words() {
+        // The caret starts at the end
+        let mut editor = editor("naïve café 日本");
+
+        editor.delete_backward(false);
+        assert_eq!(editor.text, "naïve café 日");
+        editor.delete_backward(true);
+        assert_eq!(editor.text, "naïve café ");
+        editor.delete_backward(true);
+        assert_eq!(editor.text, "naïve ");
+        assert_eq!(editor.caret, "naïve ".len());
+
+        editor.move_to(0, false);
+        editor.delete_forward(false);
+        assert_eq!(editor.text, "aïve ");
+        editor.move_to(editor.next_char(0), false);
+        editor.delete_forward(false);
+        assert_eq!(editor.text, "ave ");
+        editor.move_to(0, false);
+        editor.delete_forward(true);
+        assert_eq!(editor.text, " ");
+        assert_eq!(editor.caret, 0);
+    }
+
+    #[test]
+    fn deleting_a_selection_or_past_the_ends_stays_on_char_boundaries() {
+        let mut editor = editor("日本語");
+
+        editor.move_to(0, false);
+        editor.delete_backward(false);
+        assert_eq!(editor.text, "日本語");
+
+        editor.move_to("日".len(), false);
+        editor.move_to(editor.next_char(editor.caret), true);
+        assert_eq!(editor.selected_text(), "本");
+        // Only the selection is deleted, not the char after it
+        editor.delete_forward(false);
+        assert_eq!(editor.text, "日語");
+        assert_eq!(editor.caret, "日".len());
+
+        editor.move_to(editor.text.len(), false);
+        editor.delete_forward(true);
+        assert_eq!(editor.text, "日語");
+        editor.delete_backward(false);
+        assert_eq!(editor.text, "日");
+        assert_eq!(editor.caret, "日".len());
+    }
+}
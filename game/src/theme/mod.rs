@@ -1,10 +1,11 @@
 pub mod colors;
 use bevy::window::WindowThemeChanged;
+use serde::{Deserialize, Serialize};
 
-use crate::events::ThemeEvent;
+use crate::events::{OutputEvent, ThemeEvent, TxOutputEvent};
 use crate::prelude::*;
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ThemeMode {
     Dark,
     Light,
@@ -28,6 +29,7 @@ pub(crate) struct ThemeDidChange {
 
 #[derive(Resource, Clone)]
 pub(crate) struct Theme {
+    pub mode: ThemeMode,
     pub window_bg_color: Color,
     pub post_it_stroke_color: Color,
     pub post_it_shadow_color: Color,
@@ -35,11 +37,16 @@ pub(crate) struct Theme {
     pub debug_text_color: Color,
     pub color_swatch_text_color: Color,
     pub default_bg_color: Color,
+    pub frame_bg_color: Color,
+    pub frame_header_color: Color,
+    pub frame_stroke_color: Color,
+    pub connector_color: Color,
 }
 
 fn create_theme(mode: ThemeMode) -> Theme {
     match mode {
         ThemeMode::Light => Theme {
+            mode,
             window_bg_color: Palette::SLATE_100,
             post_it_stroke_color: Color::BLACK.with_a(0.7),
             post_it_shadow_color: Palette::GRAY_600.with_a(0.6),
@@ -47,8 +54,13 @@ fn create_theme(mode: ThemeMode) -> Theme {
             debug_text_color: Palette::BLACK,
             color_swatch_text_color: Palette::GRAY_700.with_a(0.8),
             default_bg_color: Palette::WHITE,
+            frame_bg_color: Palette::WHITE.with_a(0.6),
+            frame_header_color: Palette::SLATE_200,
+            frame_stroke_color: Palette::SLATE_300,
+            connector_color: Palette::SLATE_500,
         },
         ThemeMode::Dark => Theme {
+            mode,
             window_bg_color: Palette::SLATE_900,
             post_it_stroke_color: Color::BLACK.with_a(0.7),
             post_it_shadow_color: Palette::GRAY_600.with_a(0.9),
@@ -56,6 +68,10 @@ fn create_theme(mode: ThemeMode) -> Theme {
             debug_text_color: Palette::WHITE,
             color_swatch_text_color: Palette::GRAY_200.with_a(0.8),
             default_bg_color: Palette::SLATE_600,
+            frame_bg_color: Palette::SLATE_800.with_a(0.6),
+            frame_header_color: Palette::SLATE_700,
+            frame_stroke_color: Palette::SLATE_600,
+            connector_color: Palette::SLATE_300,
         },
     }
 }
@@ -64,14 +80,18 @@ fn on_theme_change(
     mut theme_event_reader: EventReader<ThemeEvent>,
     mut theme: ResMut<Theme>,
     mut theme_did_change_writer: EventWriter<ThemeDidChange>,
+    tx_output_event: Res<TxOutputEvent>,
 ) {
     for event in theme_event_reader.read() {
-        let new_theme = create_theme(event.theme.clone());
+        let new_theme = create_theme(event.theme);
         *theme = new_theme;
         commands.insert_resource(ClearColor(theme.window_bg_color.clone()));
         theme_did_change_writer.send(ThemeDidChange {
             theme: theme.clone(),
         });
+
+        // Themes can also change from inside the game, e.g. on undo
+        tx_output_event.emit(OutputEvent::ThemeChanged(theme.mode));
     }
 }
 
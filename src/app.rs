@@ -1,25 +1,102 @@
-use leptos::*;
+use std::{collections::HashSet, time::Duration};
+
+use leptos::{logging::warn, *};
 use leptos_meta::Html;
 
-use crate::{control_panel::ControlPanel, message_box::MessageBox, moodboard::Moodboard};
+use crate::{
+    clipboard::write_clipboard,
+    connector_panel::ConnectorPanel,
+    control_panel::ControlPanel,
+    drawing_toolbar::DrawingToolbar,
+    frame_list::FrameList,
+    image_db::persist_board_images,
+    message_box::MessageBox,
+    moodboard::Moodboard,
+    output_events::OutputEvents,
+    shape_panel::ShapePanel,
+    shortcut_sheet::ShortcutSheet,
+    status_bar::StatusBar,
+    storage::{load_keymap, save_board},
+    text_color_panel::TextColorPanel,
+};
+
+use game::{
+    events::{
+        DuplexEventsPlugin, InputEvent, KeymapEvent, OutputEvent, RxOutputEvent, Shared,
+        SharedState, TxInputEvent,
+    },
+    theme::ThemeMode,
+};
 
-use game::events::{DuplexEventsPlugin, Shared, SharedState, TxInputEvent};
+const OUTPUT_EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[component]
 pub fn App(
     events: TxInputEvent,
+    rx_output_events: RxOutputEvent,
     plugin: DuplexEventsPlugin,
     shared: Shared<SharedState>,
 ) -> impl IntoView {
     let (shared, _set_shared) = create_signal(shared.clone());
+    let persisted_images = store_value(HashSet::new());
+    let theme = expect_context::<ReadSignal<ThemeMode>>();
+    let set_theme = expect_context::<WriteSignal<ThemeMode>>();
+
+    // Every event the game sends, in order. Components subscribe to the ones they care about.
+    let output_events = OutputEvents::default();
+    provide_context(output_events);
+
+    output_events.subscribe(move |event| match event {
+        OutputEvent::BoardSnapshot(board) => {
+            let shared = shared.get_untracked();
+            let shared = shared.lock().unwrap();
+            save_board(&shared.name, board);
+            persist_board_images(board, &shared.image_store, persisted_images);
+        }
+        OutputEvent::ThemeChanged(mode) => {
+            if theme.get_untracked() != *mode {
+                set_theme.set(*mode);
+            }
+        }
+        OutputEvent::AssetLoadFailed { source, .. } => {
+            warn!("Could not load image {source}");
+        }
+        OutputEvent::CopyToClipboard(text) => write_clipboard(text.clone()),
+        _ => {}
+    });
+
+    // The game and the cheat sheet share the same shortcuts
+    let keymap = load_keymap();
+    events
+        .send(InputEvent::Keymap(KeymapEvent {
+            keymap: keymap.clone(),
+        }))
+        .expect("could not send event");
+
+    set_interval_with_handle(
+        move || {
+            for event in rx_output_events.try_iter() {
+                output_events.dispatch(&event);
+            }
+        },
+        OUTPUT_EVENT_POLL_INTERVAL,
+    )
+    .expect("could not poll output events");
 
     view! {
         <Html class="pointer-events-none" />
 
         <div class="static">
-            <ControlPanel events={events.clone()} />
-            <Moodboard plugin={plugin} shared={shared} events={events.clone()}/>
+            <ControlPanel events={events.clone()} shared={shared} />
+            <Moodboard plugin={plugin} shared={shared} events={events.clone()} keymap={keymap.clone()}/>
+            <StatusBar />
+            <FrameList events={events.clone()} />
+            <ConnectorPanel events={events.clone()} />
+            <DrawingToolbar events={events.clone()} />
+            <ShapePanel events={events.clone()} />
+            <TextColorPanel events={events.clone()} />
             <MessageBox shared={shared} />
+            <ShortcutSheet keymap={keymap} />
         </div>
     }
 }
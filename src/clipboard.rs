@@ -0,0 +1,109 @@
+use game::{
+    events::{InputEvent, PasteEvent, TxInputEvent},
+    image_store::ImageStore,
+    keymap::{Action, Keymap},
+};
+use leptos::{logging::warn, *};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::Array,
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    Blob, Clipboard, ClipboardItem, HtmlInputElement, KeyboardEvent,
+};
+
+use crate::image_file::store_image;
+
+/// Pastes the system clipboard onto the board on the paste shortcut, Ctrl+V / Cmd+V unless
+/// it was rebound. Browsers only let the clipboard be read while handling the key press.
+pub fn listen_for_paste(events: TxInputEvent, image_store: ImageStore, keymap: Keymap) {
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: KeyboardEvent| {
+        let is_paste = keymap.matches_web_key(
+            Action::Paste,
+            &event.code(),
+            event.ctrl_key() || event.meta_key(),
+            event.shift_key(),
+            event.alt_key(),
+        );
+        // Leave pasting into form fields to the browser
+        let in_input = event
+            .target()
+            .is_some_and(|target| target.dyn_ref::<HtmlInputElement>().is_some());
+        if !is_paste || in_input {
+            return;
+        }
+
+        let events = events.clone();
+        let image_store = image_store.clone();
+        spawn_local(async move {
+            match read_clipboard(&image_store).await {
+                Ok(Some(paste)) => events
+                    .send(InputEvent::Paste(paste))
+                    .expect("could not send event"),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!("Could not read the clipboard: {err:?}");
+                    // Items copied on the board can still be pasted
+                    events
+                        .send(InputEvent::Paste(PasteEvent::Copied))
+                        .expect("could not send event");
+                }
+            }
+        });
+    });
+
+    window()
+        .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        .expect("could not add event listener");
+    closure.forget();
+}
+
+/// Puts text on the system clipboard, e.g. items copied on the board.
+pub fn write_clipboard(text: String) {
+    spawn_local(async move {
+        let Some(clipboard) = window().navigator().clipboard() else {
+            warn!("The clipboard is not available");
+            return;
+        };
+
+        if let Err(err) = JsFuture::from(clipboard.write_text(&text)).await {
+            warn!("Could not write to the clipboard: {err:?}");
+        }
+    });
+}
+
+async fn read_clipboard(image_store: &ImageStore) -> Result<Option<PasteEvent>, JsValue> {
+    let clipboard = window()
+        .navigator()
+        .clipboard()
+        .ok_or_else(|| JsValue::from_str("The clipboard is not available"))?;
+
+    // Not every browser can read anything but text
+    let Ok(items) = JsFuture::from(clipboard.read()).await else {
+        return read_clipboard_text(&clipboard).await;
+    };
+
+    for item in Array::from(&items).iter() {
+        let item: ClipboardItem = item.dyn_into()?;
+        let types = Array::from(&item.types());
+
+        for mime in types.iter().filter_map(|mime| mime.as_string()) {
+            if ImageStore::extension_for_mime(&mime).is_some() {
+                let blob: Blob = JsFuture::from(item.get_type(&mime)).await?.dyn_into()?;
+                return Ok(Some(PasteEvent::Image(
+                    store_image(image_store, &blob).await?,
+                )));
+            }
+        }
+    }
+
+    read_clipboard_text(&clipboard).await
+}
+
+async fn read_clipboard_text(clipboard: &Clipboard) -> Result<Option<PasteEvent>, JsValue> {
+    let text = JsFuture::from(clipboard.read_text())
+        .await?
+        .as_string()
+        .unwrap_or_default();
+
+    Ok((!text.trim().is_empty()).then_some(PasteEvent::Text(text)))
+}
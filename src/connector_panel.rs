@@ -0,0 +1,96 @@
+use game::events::{
+    ArrowHead, ConnectorEvent, ConnectorStyle, InputEvent, ItemType, OutputEvent, TxInputEvent,
+};
+use leptos::*;
+
+use crate::{button::Button, output_events::OutputEvents};
+
+const STYLES: [(ConnectorStyle, &str); 3] = [
+    (ConnectorStyle::Straight, "Straight"),
+    (ConnectorStyle::Elbow, "Elbow"),
+    (ConnectorStyle::Curved, "Curved"),
+];
+
+const HEADS: [(ArrowHead, &str); 3] = [
+    (ArrowHead::None, "No arrow"),
+    (ArrowHead::Open, "Arrow"),
+    (ArrowHead::Filled, "Filled arrow"),
+];
+
+/// Connects two selected items, or restyles the selected connectors.
+#[component]
+pub fn ConnectorPanel(events: TxInputEvent) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (selected, set_selected) = create_signal(Vec::<ItemType>::new());
+
+    output_events.subscribe(move |event| {
+        if let OutputEvent::SelectionChanged(items) = event {
+            set_selected.set(items.iter().map(|item| item.item_type).collect());
+        }
+    });
+
+    let can_connect = move || {
+        selected.with(|selected| selected.len() == 2 && !selected.contains(&ItemType::Connector))
+    };
+    let has_connectors = move || selected.with(|selected| selected.contains(&ItemType::Connector));
+
+    // Stored, so every button can share the sender
+    let events = store_value(events);
+    let send = move |event: ConnectorEvent| {
+        events.with_value(|events| {
+            events
+                .send(InputEvent::Connector(event))
+                .expect("could not send event")
+        });
+    };
+
+    let connect_buttons = move || {
+        STYLES
+            .into_iter()
+            .map(|(style, label)| {
+                view! {
+                    <Button on:click=move |_| send(ConnectorEvent::Connect { style, head: ArrowHead::default() })>
+                        {label}
+                    </Button>
+                }
+            })
+            .collect_view()
+    };
+
+    let style_buttons = move || {
+        STYLES
+            .into_iter()
+            .map(|(style, label)| {
+                view! {
+                    <Button on:click=move |_| send(ConnectorEvent::SetStyle(style))>{label}</Button>
+                }
+            })
+            .collect_view()
+    };
+
+    let head_buttons = move || {
+        HEADS
+            .into_iter()
+            .map(|(head, label)| {
+                view! {
+                    <Button on:click=move |_| send(ConnectorEvent::SetHead(head))>{label}</Button>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <Show when=move || can_connect() || has_connectors() fallback=|| ()>
+            <div class="pointer-events-auto absolute bottom-0 right-0 flex flex-col gap-2 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 p-4 text-sm text-slate-500 dark:text-slate-400">
+                <Show when=can_connect fallback=|| ()>
+                    <p>"Connect with"</p>
+                    <div class="flex gap-2">{connect_buttons}</div>
+                </Show>
+                <Show when=has_connectors fallback=|| ()>
+                    <div class="flex gap-2">{style_buttons}</div>
+                    <div class="flex gap-2">{head_buttons}</div>
+                </Show>
+            </div>
+        </Show>
+    }
+}
@@ -1,23 +1,47 @@
-use leptos::*;
+use leptos::{html::Input, logging::warn, *};
 
 use game::{
-    events::{AddItemEvent, InputEvent, ThemeEvent, TxInputEvent},
+    events::{AddItemEvent, InputEvent, Shared, SharedState, ThemeEvent, TxInputEvent},
     theme::ThemeMode,
 };
 
 use crate::{
-    button::IconButton,
-    icons::{IconChatBubble, IconMoon, IconPencilSquare, IconStyle, IconSwatch},
+    button::{Button, IconButton},
+    icons::{
+        IconArrowUturnLeft, IconArrowUturnRight, IconChatBubble, IconMoon, IconPencilSquare,
+        IconPhoto, IconRectangleGroup, IconShapes, IconStyle, IconSwatch,
+    },
+    image_file::{store_image, store_image_url},
 };
 
+const SHAPES: [(&str, AddItemEvent); 6] = [
+    ("Ellipse", AddItemEvent::Ellipse),
+    ("Rounded rectangle", AddItemEvent::RoundedRect),
+    ("Triangle", AddItemEvent::Triangle),
+    ("Star", AddItemEvent::Star(5)),
+    ("Hexagon", AddItemEvent::Polygon(6)),
+    ("Line", AddItemEvent::Line),
+];
+
 #[component]
-pub fn ControlPanel(events: TxInputEvent) -> impl IntoView {
+pub fn ControlPanel(
+    events: TxInputEvent,
+    shared: ReadSignal<Shared<SharedState>>,
+) -> impl IntoView {
     let theme = expect_context::<ReadSignal<ThemeMode>>();
     let set_theme = expect_context::<WriteSignal<ThemeMode>>();
 
     let evt_clone = events.clone();
     let evt_clone2 = events.clone();
     let evt_clone3 = events.clone();
+    let evt_clone4 = events.clone();
+    let evt_clone5 = events.clone();
+    let evt_clone6 = events.clone();
+    let evt_clone7 = events.clone();
+    let evt_clone8 = store_value(events.clone());
+    let evt_clone9 = store_value(events.clone());
+    let file_input = create_node_ref::<Input>();
+    let url_input = create_node_ref::<Input>();
 
     let add_post_it = move |_| {
         evt_clone2
@@ -30,9 +54,8 @@ pub fn ControlPanel(events: TxInputEvent) -> impl IntoView {
     let add_swatch = move |_| {
         events
             .clone()
-            .send(InputEvent::AddItem(AddItemEvent::Swatch(String::from(
-                "#f3f4f6",
-            ))))
+            // No color picks a random one
+            .send(InputEvent::AddItem(AddItemEvent::Swatch(String::new())))
             .expect("could not send event");
     };
 
@@ -44,6 +67,115 @@ pub fn ControlPanel(events: TxInputEvent) -> impl IntoView {
             .expect("could not send event");
     };
 
+    let add_frame = move |_| {
+        evt_clone7
+            .send(InputEvent::AddItem(AddItemEvent::Frame(String::from(
+                "Frame",
+            ))))
+            .expect("could not send event");
+    };
+
+    let (shape_menu_open, set_shape_menu_open) = create_signal(false);
+
+    let add_shape = move |shape: AddItemEvent| {
+        evt_clone8.with_value(|events| {
+            events
+                .send(InputEvent::AddItem(shape))
+                .expect("could not send event")
+        });
+        set_shape_menu_open.set(false);
+    };
+
+    let shape_buttons = move || {
+        SHAPES
+            .into_iter()
+            .map(|(label, shape)| {
+                view! {
+                    <button
+                        class="px-3 py-1 rounded-full text-left text-purple-600 dark:text-white hover:bg-purple-100 dark:hover:bg-slate-700"
+                        on:click=move |_| add_shape(shape.clone())
+                    >
+                        {label}
+                    </button>
+                }
+            })
+            .collect_view()
+    };
+
+    let (image_menu_open, set_image_menu_open) = create_signal(false);
+
+    let pick_image = move |_| {
+        if let Some(input) = file_input.get() {
+            input.click();
+        }
+        set_image_menu_open.set(false);
+    };
+
+    let add_image_url = move |event: ev::SubmitEvent| {
+        event.prevent_default();
+        let Some(input) = url_input.get() else {
+            return;
+        };
+        let url = input.value().trim().to_string();
+        if url.is_empty() {
+            return;
+        }
+        let image_store = shared.get_untracked().lock().unwrap().image_store.clone();
+        let events = evt_clone9.get_value();
+
+        // The game only loads images from the store, so they are downloaded into it first
+        spawn_local(async move {
+            match store_image_url(&image_store, &url).await {
+                Ok(path) => events
+                    .send(InputEvent::AddItem(AddItemEvent::Image(path)))
+                    .expect("could not send event"),
+                Err(err) => warn!("Could not add image from {url}: {err:?}"),
+            }
+        });
+
+        input.set_value("");
+        set_image_menu_open.set(false);
+    };
+
+    let add_images = move |_| {
+        let Some(input) = file_input.get() else {
+            return;
+        };
+        let Some(files) = input.files() else {
+            return;
+        };
+        let image_store = shared.get_untracked().lock().unwrap().image_store.clone();
+
+        for file in (0..files.length()).filter_map(|index| files.get(index)) {
+            let events = evt_clone6.clone();
+            let image_store = image_store.clone();
+
+            spawn_local(async move {
+                match store_image(&image_store, &file).await {
+                    Ok(path) => events
+                        .send(InputEvent::AddItem(AddItemEvent::Image(path)))
+                        .expect("could not send event"),
+                    Err(err) => warn!("Could not add image: {err:?}"),
+                }
+            });
+        }
+
+        // Picking the same file again should add it again
+        input.set_value("");
+    };
+
+    let undo = move |_| {
+        evt_clone4
+            .send(InputEvent::Undo)
+            .expect("could not send event");
+    };
+
+    let redo = move |_| {
+        evt_clone5
+            .send(InputEvent::Redo)
+            .expect("could not send event");
+    };
+
     let toggle_theme = move |_| {
         set_theme.set(match theme() {
             ThemeMode::Light => ThemeMode::Dark,
@@ -78,14 +210,66 @@ pub fn ControlPanel(events: TxInputEvent) -> impl IntoView {
                     <IconPencilSquare />
                 </IconButton>
 
-                // <IconButton on:click=add_post_it.clone()>
-                //     <IconPhoto />
-                // </IconButton>
+                <IconButton on:click=add_frame>
+                    <IconRectangleGroup />
+                </IconButton>
+
+                <div class="relative">
+                    <IconButton on:click=move |_| set_shape_menu_open.update(|open| *open = !*open)>
+                        <IconShapes />
+                    </IconButton>
+                    <Show when=move || shape_menu_open.get() fallback=|| ()>
+                        <div class="absolute top-full left-1/2 -translate-x-1/2 mt-2 flex flex-col bg-white dark:bg-slate-800 rounded-xl shadow-lg p-2 text-sm font-semibold whitespace-nowrap">
+                            {shape_buttons}
+                        </div>
+                    </Show>
+                </div>
+
+                <div class="relative">
+                    <IconButton on:click=move |_| set_image_menu_open.update(|open| *open = !*open)>
+                        <IconPhoto />
+                    </IconButton>
+                    <Show when=move || image_menu_open.get() fallback=|| ()>
+                        <div class="absolute top-full left-1/2 -translate-x-1/2 mt-2 flex flex-col gap-2 bg-white dark:bg-slate-800 rounded-xl shadow-lg p-2 text-sm font-semibold whitespace-nowrap">
+                            <button
+                                class="px-3 py-1 rounded-full text-left text-purple-600 dark:text-white hover:bg-purple-100 dark:hover:bg-slate-700"
+                                on:click=pick_image
+                            >
+                                "From a file"
+                            </button>
+                            <form class="flex gap-2" on:submit=add_image_url>
+                                <input
+                                    type="url"
+                                    placeholder="Image URL"
+                                    class="px-3 py-1 rounded-full border-2 border-purple-200 dark:border-purple-800 bg-transparent font-normal text-slate-700 dark:text-white"
+                                    node_ref=url_input
+                                />
+                                <Button>"Add"</Button>
+                            </form>
+                        </div>
+                    </Show>
+                </div>
+                <input
+                    type="file"
+                    accept="image/png,image/jpeg"
+                    multiple
+                    class="hidden"
+                    node_ref=file_input
+                    on:change=add_images
+                />
 
                 // <Button on:click=move |_| {}>
                 //     "Update Text"
                 // </Button>
 
+                <IconButton on:click=undo>
+                    <IconArrowUturnLeft />
+                </IconButton>
+
+                <IconButton on:click=redo>
+                    <IconArrowUturnRight />
+                </IconButton>
+
                 <IconButton on:click=toggle_theme >
                     <IconMoon style={Box::new(icon)}/>
                 </IconButton>
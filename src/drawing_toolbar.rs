@@ -0,0 +1,83 @@
+use game::events::{DrawingEvent, DrawingTool, InputEvent, OutputEvent, TxInputEvent};
+use leptos::*;
+
+use crate::output_events::OutputEvents;
+
+const TOOLS: [(DrawingTool, &str); 3] = [
+    (DrawingTool::Pen, "Pen"),
+    (DrawingTool::Highlighter, "Highlighter"),
+    (DrawingTool::Eraser, "Eraser"),
+];
+
+const DEFAULT_COLOR: &str = "#ef4444";
+const DEFAULT_WIDTH: f32 = 6.0;
+
+/// Picks the drawing tool, and the color and width of new strokes.
+#[component]
+pub fn DrawingToolbar(events: TxInputEvent) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (tool, set_tool) = create_signal(None::<DrawingTool>);
+
+    // Tools can also be picked with shortcuts
+    output_events.subscribe(move |event| {
+        if let OutputEvent::DrawingToolChanged(changed) = event {
+            set_tool.set(*changed);
+        }
+    });
+
+    // Stored, so every control can share the sender
+    let events = store_value(events);
+    let send = move |event: DrawingEvent| {
+        events.with_value(|events| {
+            events
+                .send(InputEvent::Drawing(event))
+                .expect("could not send event")
+        });
+    };
+
+    let tool_buttons = move || {
+        TOOLS
+            .into_iter()
+            .map(|(button_tool, label)| {
+                let class = move || {
+                    if tool.get() == Some(button_tool) {
+                        "px-3 py-1 rounded-full text-white bg-purple-600"
+                    } else {
+                        "px-3 py-1 rounded-full text-purple-600 dark:text-white hover:bg-purple-100 dark:hover:bg-slate-700"
+                    }
+                };
+                // Clicking the picked tool goes back to selecting
+                let toggle = move |_| {
+                    let picked = (tool.get_untracked() != Some(button_tool)).then_some(button_tool);
+                    send(DrawingEvent::Tool(picked));
+                };
+
+                view! { <button class=class on:click=toggle>{label}</button> }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="pointer-events-auto absolute top-1/2 left-0 -translate-y-1/2 flex flex-col gap-2 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 p-2 text-sm font-semibold">
+            {tool_buttons}
+            <input
+                type="color"
+                value=DEFAULT_COLOR
+                class="w-full h-8 cursor-pointer bg-transparent"
+                on:input=move |event| send(DrawingEvent::Color(event_target_value(&event)))
+            />
+            <input
+                type="range"
+                min="1"
+                max="40"
+                value=DEFAULT_WIDTH
+                class="w-full accent-purple-600"
+                on:input=move |event| {
+                    if let Ok(width) = event_target_value(&event).parse::<f32>() {
+                        send(DrawingEvent::Width(width));
+                    }
+                }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,85 @@
+use game::{
+    document::ItemKind,
+    events::{FrameEvent, InputEvent, OutputEvent, TxInputEvent},
+    ItemId,
+};
+use leptos::*;
+
+use crate::output_events::OutputEvents;
+
+#[derive(Clone, Debug, PartialEq)]
+struct FrameEntry {
+    id: ItemId,
+    title: String,
+    collapsed: bool,
+}
+
+/// Lists the frames on the board, clicking one zooms to it.
+#[component]
+pub fn FrameList(events: TxInputEvent) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (frames, set_frames) = create_signal(Vec::<FrameEntry>::new());
+
+    output_events.subscribe(move |event| {
+        if let OutputEvent::BoardSnapshot(board) = event {
+            let entries = board
+                .all_items()
+                .into_iter()
+                .filter_map(|item| match &item.kind {
+                    ItemKind::Frame {
+                        title, collapsed, ..
+                    } => Some(FrameEntry {
+                        id: item.id,
+                        title: title.clone(),
+                        collapsed: *collapsed,
+                    }),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            // Snapshots arrive for every edit, most don't touch frames
+            if frames.get_untracked() != entries {
+                set_frames.set(entries);
+            }
+        }
+    });
+
+    let send = move |event: FrameEvent| {
+        events
+            .send(InputEvent::Frame(event))
+            .expect("could not send event");
+    };
+
+    view! {
+        <Show when=move || !frames.get().is_empty() fallback=|| ()>
+            <div class="pointer-events-auto absolute top-0 left-0 flex flex-col gap-1 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 p-4 text-sm text-slate-500 dark:text-slate-400">
+                <h3 class="mb-1 font-semibold text-slate-900 dark:text-white">"Frames"</h3>
+                <For
+                    each=move || frames.get()
+                    key=|frame| (frame.id, frame.title.clone(), frame.collapsed)
+                    children=move |frame| {
+                        let send = send.clone();
+                        let send_toggle = send.clone();
+                        view! {
+                            <div class="flex items-center gap-2">
+                                <button
+                                    class="w-4 text-purple-600 dark:text-purple-400"
+                                    title={if frame.collapsed { "Expand" } else { "Collapse" }}
+                                    on:click=move |_| send_toggle(FrameEvent::ToggleCollapsed(frame.id))
+                                >
+                                    {if frame.collapsed { "▸" } else { "▾" }}
+                                </button>
+                                <button
+                                    class="truncate text-left hover:text-purple-600 dark:hover:text-white"
+                                    on:click=move |_| send(FrameEvent::ZoomTo(frame.id))
+                                >
+                                    {frame.title}
+                                </button>
+                            </div>
+                        }
+                    }
+                />
+            </div>
+        </Show>
+    }
+}
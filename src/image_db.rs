@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use game::{
+    document::{BoardDocument, ItemKind},
+    image_store::ImageStore,
+};
+use leptos::{logging::warn, *};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{Array, Promise, Uint8Array},
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode,
+};
+
+const DB_NAME: &str = "moodboard";
+const DB_VERSION: u32 = 1;
+const IMAGES_STORE: &str = "images";
+
+/// Copies every persisted image into the game's [`ImageStore`].
+pub async fn load_images(image_store: &ImageStore) -> Result<(), JsValue> {
+    let store = object_store(IdbTransactionMode::Readonly).await?;
+    // Both requests have to be listened to before awaiting either, or the second one can
+    // finish before its handlers are attached
+    let keys = request_future(&store.get_all_keys()?);
+    let values = request_future(&store.get_all()?);
+    let (keys, values) = (keys.await?, values.await?);
+
+    // Both requests return their results in key order
+    for (key, value) in Array::from(&keys).iter().zip(Array::from(&values).iter()) {
+        if let Some(key) = key.as_string() {
+            image_store.restore(key, Uint8Array::new(&value).to_vec());
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores an image under its [`ImageStore`] key.
+/// Keys are content hashes, so writing the same image twice is harmless.
+pub async fn persist_image(key: &str, bytes: &[u8]) -> Result<(), JsValue> {
+    let store = object_store(IdbTransactionMode::Readwrite).await?;
+    let value = Uint8Array::from(bytes);
+    request_future(&store.put_with_key(&value, &JsValue::from_str(key))?).await?;
+
+    Ok(())
+}
+
+/// Persists the stored images a board uses that have not been written this session.
+pub fn persist_board_images(
+    board: &BoardDocument,
+    image_store: &ImageStore,
+    persisted: StoredValue<HashSet<String>>,
+) {
+    for item in board.all_items() {
+        let ItemKind::Image { source } = &item.kind else {
+            continue;
+        };
+        let Some(key) = ImageStore::key_from_asset_path(source) else {
+            continue;
+        };
+        if persisted.with_value(|persisted| persisted.contains(key)) {
+            continue;
+        }
+        let Some(bytes) = image_store.get(key) else {
+            continue;
+        };
+
+        let key = key.to_string();
+        persisted.update_value(|persisted| {
+            persisted.insert(key.clone());
+        });
+
+        spawn_local(async move {
+            if let Err(err) = persist_image(&key, &bytes).await {
+                warn!("Could not persist image {key}: {err:?}");
+            }
+        });
+    }
+}
+
+async fn object_store(mode: IdbTransactionMode) -> Result<IdbObjectStore, JsValue> {
+    open_db()
+        .await?
+        .transaction_with_str_and_mode(IMAGES_STORE, mode)?
+        .object_store(IMAGES_STORE)
+}
+
+async fn open_db() -> Result<IdbDatabase, JsValue> {
+    let factory = window()
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is not available"))?;
+    let open_request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    let upgrade_request = open_request.clone();
+    let on_upgrade_needed = Closure::once_into_js(move |_event: web_sys::Event| {
+        if let Some(db) = upgrade_db(&upgrade_request) {
+            // Without the store, images are only kept in memory until the page is closed
+            if !db.object_store_names().contains(IMAGES_STORE) {
+                if let Err(err) = db.create_object_store(IMAGES_STORE) {
+                    warn!("Could not create the image store: {err:?}");
+                }
+            }
+        }
+    });
+    open_request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+    Ok(request_future(&open_request).await?.unchecked_into())
+}
+
+fn upgrade_db(open_request: &IdbOpenDbRequest) -> Option<IdbDatabase> {
+    open_request.result().ok()?.dyn_into().ok()
+}
+
+/// Resolves with the request's result once an IndexedDB request succeeds.
+fn request_future(request: &IdbRequest) -> JsFuture {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let success_request = request.clone();
+        let on_success = Closure::once_into_js(move |_event: web_sys::Event| {
+            let result = success_request.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::UNDEFINED, &result).ok();
+        });
+
+        let error_request = request.clone();
+        let on_error = Closure::once_into_js(move |_event: web_sys::Event| {
+            let error = error_request
+                .error()
+                .ok()
+                .flatten()
+                .map(JsValue::from)
+                .unwrap_or(JsValue::UNDEFINED);
+            reject.call1(&JsValue::UNDEFINED, &error).ok();
+        });
+
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise)
+}
@@ -0,0 +1,38 @@
+use game::image_store::ImageStore;
+use leptos::window;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::Uint8Array,
+    wasm_bindgen::{JsCast, JsValue},
+    Blob, Response,
+};
+
+/// Copies an image file or blob into the [`ImageStore`] and returns the path the game loads
+/// it from.
+pub async fn store_image(image_store: &ImageStore, blob: &Blob) -> Result<String, JsValue> {
+    let mime = blob.type_();
+    let extension = ImageStore::extension_for_mime(&mime)
+        .ok_or_else(|| JsValue::from_str(&format!("Unsupported image type {mime:?}")))?;
+
+    let buffer = JsFuture::from(blob.array_buffer()).await?;
+    let key = image_store.insert(Uint8Array::new(&buffer).to_vec(), extension);
+
+    Ok(ImageStore::asset_path(&key))
+}
+
+/// Downloads an image into the [`ImageStore`].
+/// Fails for servers that don't allow cross-origin requests.
+pub async fn store_image_url(image_store: &ImageStore, url: &str) -> Result<String, JsValue> {
+    let response: Response = JsFuture::from(window().fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "Could not fetch {url}: {}",
+            response.status()
+        )));
+    }
+
+    let blob: Blob = JsFuture::from(response.blob()?).await?.dyn_into()?;
+    store_image(image_store, &blob).await
+}
@@ -1,13 +1,26 @@
 mod app;
 mod button;
+mod clipboard;
+mod connector_panel;
 mod control_panel;
+mod drawing_toolbar;
+mod frame_list;
 mod icons;
+mod image_db;
+mod image_file;
 mod message_box;
 mod moodboard;
+mod output_events;
+mod shape_panel;
+mod shortcut_sheet;
+mod status_bar;
+mod storage;
+mod text_color_panel;
 
 use app::App;
 use game::{
     events::{DuplexEventsPlugin, SharedState},
+    image_store::ImageStore,
     theme::ThemeMode,
 };
 use leptos::*;
@@ -19,10 +32,11 @@ fn main() {
     let height = window().inner_height().unwrap().as_f64().unwrap() as f32;
 
     let shared = Arc::new(Mutex::new(SharedState {
-        name: "This can be used for shared state".to_string(),
+        name: "Untitled Board".to_string(),
         window_size: (width, height),
+        image_store: ImageStore::default(),
     }));
-    let ((tx_events, _rx_events), duplex_events_plugin) = DuplexEventsPlugin::create();
+    let ((tx_events, rx_events), duplex_events_plugin) = DuplexEventsPlugin::create();
 
     let theme_signal = create_signal(ThemeMode::Light);
     let (theme, set_theme) = theme_signal;
@@ -40,6 +54,7 @@ fn main() {
             <Html class=theme_class  />
             <App
                 events={tx_events.clone()}
+                rx_output_events={rx_events.clone()}
                 plugin={duplex_events_plugin.clone()}
                 shared={shared.clone()}
             />
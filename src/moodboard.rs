@@ -1,20 +1,59 @@
-use leptos::*;
+use leptos::{logging::warn, *};
 
-use game::events::{
-    DuplexEventsPlugin, InputEvent, ResizeEvent, Shared, SharedState, TxInputEvent,
+use game::{
+    events::{
+        AddItemEvent, DropItemsEvent, DuplexEventsPlugin, InputEvent, LoadBoardEvent, ResizeEvent,
+        Shared, SharedState, TxInputEvent,
+    },
+    image_store::ImageStore,
+    keymap::{Action, Keymap},
+};
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    File,
+};
+
+use crate::{
+    clipboard::listen_for_paste,
+    image_db::load_images,
+    image_file::{store_image, store_image_url},
+    storage::load_board,
 };
-use web_sys::wasm_bindgen::{prelude::Closure, JsCast};
 
 #[component]
 pub fn Moodboard(
     plugin: DuplexEventsPlugin,
     shared: ReadSignal<Shared<SharedState>>,
     events: TxInputEvent,
+    keymap: Keymap,
 ) -> impl IntoView {
     let plugin_value = store_value(plugin);
+    let load_events = events.clone();
+    let drop_events = events.clone();
+    let paste_events = events.clone();
 
     create_effect(move |_| {
-        game::run(plugin_value.get_value(), shared.get());
+        let shared = shared.get();
+        let (name, image_store) = {
+            let shared = shared.lock().unwrap();
+            (shared.name.clone(), shared.image_store.clone())
+        };
+        let load_events = load_events.clone();
+
+        // Images have to be in the store before the board that references them is spawned
+        spawn_local(async move {
+            if let Err(err) = load_images(&image_store).await {
+                warn!("Could not load stored images: {err:?}");
+            }
+
+            load_events
+                .send(InputEvent::LoadBoard(LoadBoardEvent {
+                    board: load_board(&name),
+                }))
+                .expect("could not send event");
+        });
+
+        game::run(plugin_value.get_value(), shared);
     });
 
     let closure = Closure::<dyn FnMut(_)>::new(move |_event: web_sys::Event| {
@@ -33,7 +72,101 @@ pub fn Moodboard(
         .expect("could not add event listener");
     closure.forget();
 
+    listen_for_paste(
+        paste_events,
+        shared.get_untracked().lock().unwrap().image_store.clone(),
+        keymap.clone(),
+    );
+
+    // The game duplicates on Ctrl+D / Cmd+D, which the browser would bookmark the page on
+    window_event_listener(ev::keydown, move |event| {
+        if keymap.matches_web_key(
+            Action::Duplicate,
+            &event.code(),
+            event.ctrl_key() || event.meta_key(),
+            event.shift_key(),
+            event.alt_key(),
+        ) {
+            event.prevent_default();
+        }
+    });
+
+    let on_drop = move |event: ev::DragEvent| {
+        event.prevent_default();
+        let Some(data_transfer) = event.data_transfer() else {
+            return;
+        };
+
+        let files = data_transfer
+            .files()
+            .map(|files| {
+                (0..files.length())
+                    .filter_map(|index| files.get(index))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let uri_list = data_transfer.get_data("text/uri-list").unwrap_or_default();
+        let text = data_transfer.get_data("text/plain").unwrap_or_default();
+
+        // The canvas covers the window, so canvas offsets are window coordinates
+        let (x, y) = (event.offset_x() as f32, event.offset_y() as f32);
+        let image_store = shared.get_untracked().lock().unwrap().image_store.clone();
+        let events = drop_events.clone();
+
+        spawn_local(async move {
+            let items = dropped_items(&image_store, files, &uri_list, text).await;
+            if !items.is_empty() {
+                events
+                    .send(InputEvent::DropItems(DropItemsEvent { x, y, items }))
+                    .expect("could not send event");
+            }
+        });
+    };
+
     view! {
-        <canvas id="bevy" class="pointer-events-auto absolute top-0 left-0 -z-10"></canvas>
+        <canvas
+            id="bevy"
+            class="pointer-events-auto absolute top-0 left-0 -z-10"
+            // The browser only allows dropping onto elements that cancel `dragover`
+            on:dragover=move |event: ev::DragEvent| event.prevent_default()
+            on:drop=on_drop
+        ></canvas>
+    }
+}
+
+/// Turns dropped files into images, and anything else into a single post-it.
+async fn dropped_items(
+    image_store: &ImageStore,
+    files: Vec<File>,
+    uri_list: &str,
+    text: String,
+) -> Vec<AddItemEvent> {
+    let mut items = Vec::new();
+
+    for file in files {
+        match store_image(image_store, &file).await {
+            Ok(path) => items.push(AddItemEvent::Image(path)),
+            Err(err) => warn!("Could not add dropped file {}: {err:?}", file.name()),
+        }
     }
+
+    // Images dragged from other pages arrive as links
+    if items.is_empty() {
+        let urls = uri_list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        for url in urls {
+            if let Ok(path) = store_image_url(image_store, url).await {
+                items.push(AddItemEvent::Image(path));
+            }
+        }
+    }
+
+    if items.is_empty() && !text.trim().is_empty() {
+        items.push(AddItemEvent::PostIt(text));
+    }
+
+    items
 }
@@ -0,0 +1,36 @@
+use std::rc::Rc;
+
+use game::events::OutputEvent;
+use leptos::*;
+
+type Subscriber = Rc<dyn Fn(&OutputEvent)>;
+
+/// Hands every event the game sends to each subscriber, in order.
+/// Events are passed by reference, so subscribers only clone what they keep.
+#[derive(Clone, Copy)]
+pub struct OutputEvents {
+    subscribers: StoredValue<Vec<Subscriber>>,
+}
+
+impl Default for OutputEvents {
+    fn default() -> Self {
+        Self {
+            subscribers: store_value(Vec::new()),
+        }
+    }
+}
+
+impl OutputEvents {
+    /// Calls `subscriber` with every event sent from now on.
+    pub fn subscribe(&self, subscriber: impl Fn(&OutputEvent) + 'static) {
+        self.subscribers
+            .update_value(|subscribers| subscribers.push(Rc::new(subscriber)));
+    }
+
+    pub fn dispatch(&self, event: &OutputEvent) {
+        // Cloned out, so subscribers can subscribe while an event is handed out
+        for subscriber in self.subscribers.get_value() {
+            subscriber(event);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use game::events::{InputEvent, ItemType, OutputEvent, ShapeEvent, TxInputEvent};
+use leptos::*;
+
+use crate::{button::Button, output_events::OutputEvents};
+
+const DEFAULT_FILL: &str = "#bae6fd";
+const DEFAULT_STROKE: &str = "#0369a1";
+const DEFAULT_STROKE_WIDTH: f32 = 4.0;
+
+/// Restyles the selected shapes: their fill, outline and corners.
+#[component]
+pub fn ShapePanel(events: TxInputEvent) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (has_shapes, set_has_shapes) = create_signal(false);
+
+    output_events.subscribe(move |event| {
+        if let OutputEvent::SelectionChanged(items) = event {
+            set_has_shapes.set(items.iter().any(|item| item.item_type == ItemType::Shape));
+        }
+    });
+
+    // Stored, so every control can share the sender
+    let events = store_value(events);
+    let send = move |event: ShapeEvent| {
+        events.with_value(|events| {
+            events
+                .send(InputEvent::Shape(event))
+                .expect("could not send event")
+        });
+    };
+
+    view! {
+        <Show when=move || has_shapes.get() fallback=|| ()>
+            <div class="pointer-events-auto absolute top-0 right-0 flex flex-col gap-2 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 p-4 text-sm text-slate-500 dark:text-slate-400">
+                <label class="flex items-center justify-between gap-4">
+                    "Fill"
+                    <input
+                        type="color"
+                        value=DEFAULT_FILL
+                        class="w-12 h-8 cursor-pointer bg-transparent"
+                        on:input=move |event| send(ShapeEvent::Fill(event_target_value(&event)))
+                    />
+                </label>
+                <Button on:click=move |_| send(ShapeEvent::Fill(String::from("none")))>
+                    "No fill"
+                </Button>
+                <label class="flex items-center justify-between gap-4">
+                    "Outline"
+                    <input
+                        type="color"
+                        value=DEFAULT_STROKE
+                        class="w-12 h-8 cursor-pointer bg-transparent"
+                        on:input=move |event| send(ShapeEvent::Stroke(event_target_value(&event)))
+                    />
+                </label>
+                <label class="flex flex-col gap-1">
+                    "Outline width"
+                    <input
+                        type="range"
+                        min="0"
+                        max="24"
+                        value=DEFAULT_STROKE_WIDTH
+                        class="w-full accent-purple-600"
+                        on:input=move |event| {
+                            if let Ok(width) = event_target_value(&event).parse::<f32>() {
+                                send(ShapeEvent::StrokeWidth(width));
+                            }
+                        }
+                    />
+                </label>
+                <label class="flex flex-col gap-1">
+                    "Corner radius"
+                    <input
+                        type="range"
+                        min="0"
+                        max="150"
+                        value="0"
+                        class="w-full accent-purple-600"
+                        on:input=move |event| {
+                            if let Ok(radius) = event_target_value(&event).parse::<f32>() {
+                                send(ShapeEvent::CornerRadius(radius));
+                            }
+                        }
+                    />
+                </label>
+            </div>
+        </Show>
+    }
+}
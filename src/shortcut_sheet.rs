@@ -0,0 +1,86 @@
+use game::{events::OutputEvent, keymap::Keymap};
+use leptos::*;
+
+use crate::output_events::OutputEvents;
+
+/// Lists every shortcut, opened by the game's "show shortcuts" shortcut.
+#[component]
+pub fn ShortcutSheet(keymap: Keymap) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (open, set_open) = create_signal(false);
+
+    output_events.subscribe(move |event| {
+        if let OutputEvent::ShowShortcuts = event {
+            set_open.update(|open| *open = !*open);
+        }
+    });
+
+    window_event_listener(ev::keydown, move |event| {
+        if event.key() == "Escape" {
+            set_open.set(false);
+        }
+    });
+
+    let mac = window()
+        .navigator()
+        .platform()
+        .is_ok_and(|platform| platform.starts_with("Mac"));
+
+    // Actions are listed group by group, so a new group starts wherever it changes
+    let mut groups: Vec<(&'static str, Vec<(&'static str, String)>)> = Vec::new();
+    for (action, chords) in keymap.bindings() {
+        let chords = chords
+            .iter()
+            .map(|chord| chord.label(mac))
+            .collect::<Vec<_>>()
+            .join(" / ");
+
+        match groups.last_mut() {
+            Some((group, actions)) if *group == action.group() => {
+                actions.push((action.label(), chords))
+            }
+            _ => groups.push((action.group(), vec![(action.label(), chords)])),
+        }
+    }
+
+    let groups = store_value(groups);
+    let group_views = move || {
+        groups
+            .get_value()
+            .into_iter()
+            .map(|(group, actions)| {
+                view! {
+                    <div>
+                        <h3 class="mb-2 font-semibold text-slate-900 dark:text-white">{group}</h3>
+                        <dl class="grid grid-cols-2 gap-x-6 gap-y-1">
+                            {actions
+                                .into_iter()
+                                .map(|(label, chords)| {
+                                    view! {
+                                        <dt>{label}</dt>
+                                        <dd class="font-mono text-right text-purple-600 dark:text-purple-400">
+                                            {if chords.is_empty() { "—".to_string() } else { chords }}
+                                        </dd>
+                                    }
+                                })
+                                .collect_view()}
+                        </dl>
+                    </div>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <Show when=move || open.get() fallback=|| ()>
+            <div
+                class="pointer-events-auto fixed inset-0 flex items-center justify-center bg-black/30"
+                on:click=move |_| set_open.set(false)
+            >
+                <div class="flex gap-8 bg-white dark:bg-slate-800 rounded-xl shadow-lg p-6 text-sm text-slate-500 dark:text-slate-400">
+                    {group_views}
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,32 @@
+use game::events::OutputEvent;
+use leptos::*;
+
+use crate::output_events::OutputEvents;
+
+#[component]
+pub fn StatusBar() -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (selected, set_selected) = create_signal(0);
+    let (scale, set_scale) = create_signal(1.0);
+
+    output_events.subscribe(move |event| match event {
+        OutputEvent::SelectionChanged(items) => set_selected.set(items.len()),
+        OutputEvent::CameraChanged { scale, .. } => set_scale.set(*scale),
+        _ => {}
+    });
+
+    let selection = move || match selected.get() {
+        0 => "Nothing selected".to_string(),
+        1 => "1 item selected".to_string(),
+        count => format!("{count} items selected"),
+    };
+    // A camera scale of 1 shows the board at its actual size
+    let zoom = move || format!("{:.0}%", 100.0 / scale.get());
+
+    view! {
+        <div class="pointer-events-auto absolute bottom-0 left-0 flex gap-4 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 px-4 py-2 text-sm text-slate-500 dark:text-slate-400">
+            <p>{selection}</p>
+            <p>{zoom}</p>
+        </div>
+    }
+}
@@ -0,0 +1,56 @@
+use game::{document::BoardDocument, keymap::Keymap};
+use leptos::{logging::warn, *};
+
+const STORAGE_PREFIX: &str = "moodboard";
+
+fn storage_key(name: &str) -> String {
+    format!("{STORAGE_PREFIX}:{name}")
+}
+
+pub fn load_board(name: &str) -> Option<BoardDocument> {
+    let storage = window().local_storage().ok().flatten()?;
+    let json = storage.get_item(&storage_key(name)).ok().flatten()?;
+
+    match BoardDocument::from_json(&json) {
+        Ok(board) => Some(board),
+        Err(err) => {
+            warn!("Could not parse saved board \"{name}\": {err}");
+            None
+        }
+    }
+}
+
+pub fn save_board(name: &str, board: &BoardDocument) {
+    let Some(storage) = window().local_storage().ok().flatten() else {
+        warn!("localStorage is not available, board was not saved");
+        return;
+    };
+
+    match board.to_json() {
+        Ok(json) => {
+            if storage.set_item(&storage_key(name), &json).is_err() {
+                warn!("Could not save board \"{name}\"");
+            }
+        }
+        Err(err) => warn!("Could not serialize board \"{name}\": {err}"),
+    }
+}
+
+/// The default shortcuts with the user's overrides applied, see [`Keymap::apply_overrides`].
+pub fn load_keymap() -> Keymap {
+    let mut keymap = Keymap::default();
+    let overrides = window().local_storage().ok().flatten().and_then(|storage| {
+        storage
+            .get_item(&format!("{STORAGE_PREFIX}-keymap"))
+            .ok()
+            .flatten()
+    });
+
+    if let Some(json) = overrides {
+        if let Err(err) = keymap.apply_overrides(&json) {
+            warn!("Could not parse the keymap overrides: {err}");
+        }
+    }
+
+    keymap
+}
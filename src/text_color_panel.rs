@@ -0,0 +1,47 @@
+use game::events::{InputEvent, OutputEvent, SpanColorEvent, TxInputEvent};
+use leptos::*;
+
+use crate::{button::Button, output_events::OutputEvents};
+
+const DEFAULT_COLOR: &str = "#374151";
+
+/// Colors the selected text, or what is typed next, while a post-it or text is edited.
+#[component]
+pub fn TextColorPanel(events: TxInputEvent) -> impl IntoView {
+    let output_events = expect_context::<OutputEvents>();
+    let (is_editing, set_is_editing) = create_signal(false);
+
+    output_events.subscribe(move |event| {
+        if let OutputEvent::TextEditingChanged(editing) = event {
+            set_is_editing.set(*editing);
+        }
+    });
+
+    let events = store_value(events);
+    let send = move |color: String| {
+        events.with_value(|events| {
+            events
+                .send(InputEvent::SpanColor(SpanColorEvent { color }))
+                .expect("could not send event")
+        });
+    };
+
+    view! {
+        <Show when=move || is_editing.get() fallback=|| ()>
+            <div class="pointer-events-auto absolute top-0 right-0 flex flex-col gap-2 bg-white dark:bg-slate-800 rounded-xl shadow-lg m-4 p-4 text-sm text-slate-500 dark:text-slate-400">
+                <label class="flex items-center justify-between gap-4">
+                    "Text color"
+                    <input
+                        type="color"
+                        value=DEFAULT_COLOR
+                        class="w-12 h-8 cursor-pointer bg-transparent"
+                        on:input=move |event| send(event_target_value(&event))
+                    />
+                </label>
+                <Button on:click=move |_| send(String::from("none"))>
+                    "Theme color"
+                </Button>
+            </div>
+        </Show>
+    }
+}